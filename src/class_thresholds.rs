@@ -0,0 +1,366 @@
+use crate::filter_buildings::NmsMode;
+use crate::image_data_wrapper::{Building, DatasetType};
+use crate::prelude::*;
+
+use std::collections::BTreeMap;
+
+// Ab dieser IoU zählt eine Vorhersage beim Auto-Tune als Treffer (wie mAP50)
+pub const MATCH_IOU: f32 = 0.5;
+
+/// Min-Confidence pro Klasse, liegt als `class_thresholds.yaml` beim Modell.
+/// Klassen ohne Eintrag benutzen den globalen Wert aus dem Model Test.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ClassThresholds {
+    pub thresholds: BTreeMap<String, f32>,
+}
+
+impl ClassThresholds {
+    pub fn path(model_name: &str) -> String {
+        format!("runs/detect/{}/class_thresholds.yaml", model_name)
+    }
+
+    /// Fehlende Datei = keine eigenen Schwellen.
+    pub fn load(model_name: &str) -> ClassThresholds {
+        fs::read_to_string(ClassThresholds::path(model_name))
+            .ok()
+            .and_then(|s| serde_yaml::from_str(&s).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, model_name: &str) -> Result<(), FofError> {
+        if ensemble::Ensemble::find(model_name).is_some() {
+            return Err(FofError::Failed(format!(
+                "{} ist ein Ensemble und hat keinen Ordner für Schwellen",
+                model_name
+            )));
+        }
+
+        let yaml =
+            serde_yaml::to_string(self).map_err(|e| FofError::YamlParseError(e.to_string()))?;
+        fs::write(ClassThresholds::path(model_name), yaml)?;
+        Ok(())
+    }
+
+    pub fn get(&self, class_name: &str, fallback: f32) -> f32 {
+        self.thresholds.get(class_name).copied().unwrap_or(fallback)
+    }
+}
+
+/// Ergebnis vom Auto-Tune für eine Klasse.
+#[derive(Debug, Clone)]
+pub struct ClassTuning {
+    pub class_name: String,
+    pub threshold: f32,
+    pub f1: f32,
+    pub precision: f32,
+    pub recall: f32,
+    pub ground_truth: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct TuneProgress {
+    pub done: usize,
+    pub total: usize,
+}
+
+#[derive(Deserialize)]
+struct DataYamlVal {
+    val: String,
+}
+
+/// Bilder im Val-Split mit der passenden Label-Datei (ultralytics: `images/` → `labels/`, `.txt`).
+fn val_images(dataset_type: &DatasetType) -> Result<Vec<(PathBuf, PathBuf)>, FofError> {
+    let data_yaml = dataset_type.data_yaml();
+    let content = fs::read_to_string(&data_yaml)
+        .map_err(|_| FofError::FailedReadingFile(data_yaml.clone()))?;
+    let split: DataYamlVal =
+        serde_yaml::from_str(&content).map_err(|e| FofError::YamlParseError(e.to_string()))?;
+
+    let images_dir = Path::new(&dataset_type.dir()).join(&split.val);
+    let labels_dir = Path::new(&dataset_type.dir()).join(split.val.replacen("images", "labels", 1));
+
+    let entries = fs::read_dir(&images_dir)
+        .map_err(|_| FofError::FailedReadingDirectory(images_dir.display().to_string()))?;
+
+    let mut images: Vec<(PathBuf, PathBuf)> = entries
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| {
+            p.extension()
+                .map(|x| {
+                    ["png", "jpg", "jpeg", "webp"]
+                        .contains(&x.to_string_lossy().to_lowercase().as_str())
+                })
+                .unwrap_or(false)
+        })
+        .map(|p| {
+            let label = labels_dir.join(format!(
+                "{}.txt",
+                p.file_stem().unwrap_or_default().to_string_lossy()
+            ));
+            (p, label)
+        })
+        .collect();
+
+    images.sort();
+    Ok(images)
+}
+
+/// YOLO Labels (normalisiert, Mitte + Größe) in Pixel-Boxen wie bei `Building`.
+fn read_labels(label_path: &Path, width: f32, height: f32) -> Vec<(i32, (f32, f32, f32, f32))> {
+    let Ok(content) = fs::read_to_string(label_path) else {
+        return vec![]; // Bild ohne Label-Datei = keine Objekte
+    };
+
+    content
+        .lines()
+        .filter_map(|line| {
+            let values: Vec<f32> = line
+                .split_whitespace()
+                .filter_map(|v| v.parse().ok())
+                .collect();
+            let [class_id, cx, cy, w, h] = values[..] else {
+                return None;
+            };
+
+            Some((
+                class_id as i32,
+                (
+                    (cx - w / 2.0) * width,
+                    (cy - h / 2.0) * height,
+                    (cx + w / 2.0) * width,
+                    (cy + h / 2.0) * height,
+                ),
+            ))
+        })
+        .collect()
+}
+
+/// (Confidence, Treffer?) für jede Vorhersage, gierig nach Confidence zugeordnet.
+fn match_predictions(
+    predictions: &[Building],
+    labels: &[(i32, (f32, f32, f32, f32))],
+) -> Vec<(i32, f32, bool)> {
+    let mut sorted = predictions.to_vec();
+    sorted.sort_by(|a, b| {
+        b.confidence
+            .partial_cmp(&a.confidence)
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+
+    let mut used = vec![false; labels.len()];
+    sorted
+        .iter()
+        .map(|prediction| {
+            let best = labels
+                .iter()
+                .enumerate()
+                .filter(|(i, (class_id, _))| !used[*i] && *class_id == prediction.class_id)
+                .map(|(i, (_, bbox))| {
+                    (
+                        i,
+                        filter_buildings::get_similarity(prediction.bounding_box, *bbox),
+                    )
+                })
+                .filter(|(_, iou)| *iou >= MATCH_IOU)
+                .max_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal));
+
+            if let Some((i, _)) = best {
+                used[i] = true;
+            }
+            (prediction.class_id, prediction.confidence, best.is_some())
+        })
+        .collect()
+}
+
+/// Höchster F1 über alle Schwellen, die in den Vorhersagen vorkommen.
+fn best_threshold(
+    mut matches: Vec<(f32, bool)>,
+    ground_truth: usize,
+) -> Option<(f32, f32, f32, f32)> {
+    if ground_truth == 0 || matches.is_empty() {
+        return None;
+    }
+
+    matches.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(std::cmp::Ordering::Equal));
+
+    let mut best: Option<(f32, f32, f32, f32)> = None;
+    let (mut tp, mut fp) = (0usize, 0usize);
+
+    for (i, (confidence, hit)) in matches.iter().enumerate() {
+        if *hit {
+            tp += 1;
+        } else {
+            fp += 1;
+        }
+
+        // Gleiche Confidences gehören zur gleichen Schwelle
+        if matches
+            .get(i + 1)
+            .map(|next| next.0 == *confidence)
+            .unwrap_or(false)
+        {
+            continue;
+        }
+
+        let precision = tp as f32 / (tp + fp) as f32;
+        let recall = tp as f32 / ground_truth as f32;
+        let f1 = if precision + recall > 0.0 {
+            2.0 * precision * recall / (precision + recall)
+        } else {
+            0.0
+        };
+
+        if best.map(|b| f1 > b.1).unwrap_or(true) {
+            best = Some((*confidence, f1, precision, recall));
+        }
+    }
+
+    best
+}
+
+/// Sucht pro Klasse die Schwelle mit dem besten F1 auf dem Val-Split.
+/// Die Vorhersagen gehen durch das gleiche NMS wie im Model Test, damit die Schwellen dazu passen.
+/// Klassen ohne Labels im Val-Split oder ohne Vorhersagen tauchen im Ergebnis nicht auf.
+pub fn auto_tune(
+    model_name: &str,
+    nms_mode: NmsMode,
+    nms_iou: f32,
+    cancel: &AtomicBool,
+    mut on_progress: impl FnMut(TuneProgress),
+) -> Result<Vec<ClassTuning>, FofError> {
+    let dataset_type = image_data_wrapper::get_dataset_type(model_name)?;
+    let names: HashMap<i32, String> = image_data_wrapper::read_class_names(&dataset_type)?
+        .into_iter()
+        .map(|(id, name)| (id as i32, name))
+        .collect();
+
+    let images = val_images(&dataset_type)?;
+    if images.is_empty() {
+        return Err(FofError::Failed(format!(
+            "Keine Bilder im Val-Split von {}",
+            dataset_type.name
+        )));
+    }
+
+    let mut matches: HashMap<i32, Vec<(f32, bool)>> = HashMap::new();
+    let mut ground_truth: HashMap<i32, usize> = HashMap::new();
+
+    for (i, (image_path, label_path)) in images.iter().enumerate() {
+        if cancel.load(Ordering::SeqCst) {
+            return Err(FofError::Failed("Auto-Tune abgebrochen".to_string()));
+        }
+
+        let image_path = image_path.display().to_string();
+        let (width, height) = image::image_dimensions(&image_path)
+            .map_err(|e| FofError::FailedReadingFile(format!("{}: {}", image_path, e)))?;
+
+        let labels = read_labels(label_path, width as f32, height as f32);
+        for (class_id, _) in labels.iter() {
+            *ground_truth.entry(*class_id).or_default() += 1;
+        }
+
+        let predictions = image_data_wrapper::get_prediction(model_name, &image_path)?;
+        let predictions = filter_buildings::nms(&predictions, nms_mode, nms_iou);
+
+        for (class_id, confidence, hit) in match_predictions(&predictions, &labels) {
+            matches.entry(class_id).or_default().push((confidence, hit));
+        }
+
+        on_progress(TuneProgress {
+            done: i + 1,
+            total: images.len(),
+        });
+    }
+
+    let mut result: Vec<ClassTuning> = matches
+        .into_iter()
+        .filter_map(|(class_id, class_matches)| {
+            let gt = ground_truth.get(&class_id).copied().unwrap_or(0);
+            let (threshold, f1, precision, recall) = best_threshold(class_matches, gt)?;
+            Some(ClassTuning {
+                class_name: names
+                    .get(&class_id)
+                    .cloned()
+                    .unwrap_or_else(|| class_id.to_string()),
+                threshold,
+                f1,
+                precision,
+                recall,
+                ground_truth: gt,
+            })
+        })
+        .collect();
+
+    result.sort_by(|a, b| a.class_name.cmp(&b.class_name));
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn building(class_id: i32, confidence: f32, bbox: (f32, f32, f32, f32)) -> Building {
+        Building {
+            class_id,
+            class_name: format!("klasse_{}", class_id),
+            confidence,
+            bounding_box: bbox,
+        }
+    }
+
+    #[test]
+    fn match_predictions_uses_each_label_once() {
+        let labels = vec![(0, (0.0, 0.0, 10.0, 10.0)), (0, (20.0, 0.0, 30.0, 10.0))];
+        let predictions = vec![
+            building(0, 0.6, (20.0, 0.0, 30.0, 10.0)),
+            building(0, 0.8, (1.0, 0.0, 11.0, 10.0)), // Duplikat vom ersten Label
+            building(1, 0.7, (20.0, 0.0, 30.0, 10.0)), // falsche Klasse
+            building(0, 0.9, (0.0, 0.0, 10.0, 10.0)),
+        ];
+
+        let matches = match_predictions(&predictions, &labels);
+        assert_eq!(
+            matches,
+            vec![
+                (0, 0.9, true),
+                (0, 0.8, false),
+                (1, 0.7, false),
+                (0, 0.6, true)
+            ]
+        );
+    }
+
+    #[test]
+    fn best_threshold_maximises_f1() {
+        let matches = vec![
+            (0.3, false),
+            (0.9, true),
+            (0.7, false),
+            (0.8, true),
+            (0.6, true),
+        ];
+        let (threshold, f1, precision, recall) = best_threshold(matches, 4).unwrap();
+        assert_eq!(threshold, 0.6);
+        assert!((f1 - 0.75).abs() < 1e-6);
+        assert!((precision - 0.75).abs() < 1e-6);
+        assert!((recall - 0.75).abs() < 1e-6);
+    }
+
+    #[test]
+    fn best_threshold_treats_equal_confidences_as_one_threshold() {
+        // Bei 0.8 gibt es nur beide Vorhersagen zusammen, nie nur den Treffer
+        let (threshold, _, precision, recall) =
+            best_threshold(vec![(0.8, true), (0.8, false)], 1).unwrap();
+        assert_eq!(threshold, 0.8);
+        assert_eq!(precision, 0.5);
+        assert_eq!(recall, 1.0);
+    }
+
+    #[test]
+    fn best_threshold_needs_labels_and_predictions() {
+        assert_eq!(best_threshold(vec![(0.5, false)], 0), None);
+        assert_eq!(best_threshold(vec![], 3), None);
+    }
+}
@@ -0,0 +1,130 @@
+use crate::prelude::*;
+
+use std::sync::RwLock;
+use std::time::{Duration, SystemTime};
+
+// Ordner, die beim Start älter sind, gehören zu abgestürzten / alten Sessions
+pub const STALE_AFTER: Duration = Duration::from_secs(10 * 60);
+
+// Liegt in jedem Workspace, damit beim Aufräumen nur eigene Ordner gelöscht werden
+pub const WORKSPACE_MARKER: &str = ".fof_workspace";
+
+static ROOT: RwLock<Option<PathBuf>> = RwLock::new(None);
+
+/// Ordner unter dem alle Anfragen ihren Workspace bekommen.
+/// Standard ist `Communication`, kann mit `FOF_COMMUNICATION_DIR` oder in den Einstellungen geändert werden.
+/// Der Ordner aus den Einstellungen wird mit `load_root` geladen.
+pub fn root() -> PathBuf {
+    if let Some(root) = ROOT.read().unwrap().clone() {
+        return root;
+    }
+
+    std::env::var("FOF_COMMUNICATION_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| PathBuf::from("Communication"))
+}
+
+/// Übernimmt den gespeicherten Ordner, außer `FOF_COMMUNICATION_DIR` ist gesetzt.
+pub fn load_root() {
+    if std::env::var("FOF_COMMUNICATION_DIR").is_ok() {
+        return;
+    }
+    if let Some(path) = folder_settings::FolderSettings::load().communication_dir {
+        *ROOT.write().unwrap() = Some(path);
+    }
+}
+
+pub fn set_root(path: PathBuf) -> Result<(), FofError> {
+    *ROOT.write().unwrap() = Some(path.clone());
+    folder_settings::FolderSettings::update(|s| s.communication_dir = Some(path))
+}
+
+/// Ein eigener Ordner pro Vorhersage-Anfrage (`<root>/<model>_<uuid>`).
+/// Wird beim Droppen wieder gelöscht, auch wenn die Anfrage fehlschlägt.
+pub struct Workspace {
+    path: PathBuf,
+}
+
+impl Workspace {
+    pub fn create(model_name: &str) -> Result<Self, FofError> {
+        let path = root().join(format!("{}_{}", model_name, uuid::Uuid::new_v4()));
+
+        fs::create_dir_all(&path)
+            .and_then(|_| fs::write(path.join(WORKSPACE_MARKER), ""))
+            .map_err(|e| {
+                FofError::FailedCreatingCommunication(format!("{}: {}", path.display(), e))
+            })?;
+
+        Ok(Workspace { path })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn file(&self, name: &str) -> String {
+        self.path.join(name).display().to_string()
+    }
+}
+
+impl Drop for Workspace {
+    fn drop(&mut self) {
+        if let Err(e) = fs::remove_dir_all(&self.path) {
+            eprintln!(
+                "Konnte Workspace {} nicht löschen: {}",
+                self.path.display(),
+                e
+            );
+        }
+    }
+}
+
+/// Sieht aus wie von `Workspace::create`: `<model>_<uuid>` mit Marker-Datei.
+fn is_workspace(path: &Path) -> bool {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    let Some((model, id)) = name.rsplit_once('_') else {
+        return false;
+    };
+
+    !model.is_empty() && uuid::Uuid::parse_str(id).is_ok() && path.join(WORKSPACE_MARKER).is_file()
+}
+
+/// Löscht alle Workspaces im Root, die älter als `max_age` sind. Gibt die Anzahl zurück.
+/// Andere Ordner bleiben liegen, der Root kann ja auf einen beliebigen Ordner zeigen.
+pub fn collect_stale_workspaces(max_age: Duration) -> Result<usize, FofError> {
+    let root = root();
+    if !root.exists() {
+        return Ok(0);
+    }
+
+    let entries = fs::read_dir(&root)
+        .map_err(|_| FofError::FailedReadingDirectory(root.display().to_string()))?;
+
+    let now = SystemTime::now();
+    let mut removed = 0;
+
+    for entry in entries.flatten() {
+        let Ok(metadata) = entry.metadata() else {
+            continue;
+        };
+        if !metadata.is_dir() || !is_workspace(&entry.path()) {
+            continue;
+        }
+
+        let age = metadata
+            .modified()
+            .ok()
+            .and_then(|m| now.duration_since(m).ok())
+            .unwrap_or_default();
+
+        if age < max_age {
+            continue;
+        }
+
+        fs::remove_dir_all(entry.path())
+            .map_err(|_| FofError::FailedDeletingDirectory(entry.path().display().to_string()))?;
+        removed += 1;
+    }
+
+    Ok(removed)
+}
@@ -0,0 +1,166 @@
+use crate::image_data_wrapper::{Building, DatasetType, Model, ModelMetadata, ModelStatus};
+use crate::prelude::*;
+
+use std::sync::RwLock;
+
+// Ensembles sind virtuelle Modelle ohne eigenen Ordner in runs/detect
+pub const ENSEMBLES_FILE: &str = "ensembles.yaml";
+
+// `find` läuft bei jeder Vorhersage, deshalb wird die Datei nur einmal gelesen
+static ENSEMBLES: RwLock<Option<Vec<Ensemble>>> = RwLock::new(None);
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EnsembleMember {
+    pub model: String,
+    pub weight: f32,
+}
+
+/// Mehrere Modelle vom gleichen Datensatz, deren Vorhersagen mit Weighted Box Fusion
+/// zusammengelegt werden. Der Name kann überall benutzt werden, wo ein Modellname erwartet wird.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Ensemble {
+    pub name: String,
+    pub dataset_type: String,
+    pub members: Vec<EnsembleMember>,
+    pub iou_threshold: f32,
+}
+
+impl Ensemble {
+    /// Wird beim ersten Aufruf gelesen, danach aktuell gehalten (siehe `save_all` und `reload`).
+    pub fn load_all() -> Vec<Ensemble> {
+        if let Some(ensembles) = ENSEMBLES.read().unwrap().as_ref() {
+            return ensembles.clone();
+        }
+
+        let ensembles = Ensemble::read_file();
+        *ENSEMBLES.write().unwrap() = Some(ensembles.clone());
+        ensembles
+    }
+
+    /// Für Änderungen an `ENSEMBLES_FILE` von Hand.
+    pub fn reload() {
+        *ENSEMBLES.write().unwrap() = None;
+    }
+
+    fn read_file() -> Vec<Ensemble> {
+        fs::read_to_string(ENSEMBLES_FILE)
+            .ok()
+            .and_then(|s| match serde_yaml::from_str(&s) {
+                Ok(ensembles) => Some(ensembles),
+                Err(e) => {
+                    eprintln!("{} ist kaputt: {}", ENSEMBLES_FILE, e);
+                    None
+                }
+            })
+            .unwrap_or_default()
+    }
+
+    fn save_all(ensembles: &[Ensemble]) -> Result<(), FofError> {
+        let yaml = serde_yaml::to_string(ensembles)
+            .map_err(|e| FofError::YamlParseError(e.to_string()))?;
+        fs::write(ENSEMBLES_FILE, yaml)?;
+        *ENSEMBLES.write().unwrap() = Some(ensembles.to_vec());
+        Ok(())
+    }
+
+    pub fn find(name: &str) -> Option<Ensemble> {
+        Ensemble::load_all().into_iter().find(|e| e.name == name)
+    }
+
+    /// Prüft Name und Mitglieder und speichert das Ensemble in `ENSEMBLES_FILE`.
+    pub fn create(&self) -> Result<(), FofError> {
+        if self.name.trim().is_empty() {
+            return Err(FofError::Failed("Ensemble braucht einen Namen".to_string()));
+        }
+        if Path::new(&format!("runs/detect/{}", self.name)).exists()
+            || Ensemble::find(&self.name).is_some()
+        {
+            return Err(FofError::ModelAlreadyExists);
+        }
+        if self.members.len() < 2 {
+            return Err(FofError::Failed(
+                "Ein Ensemble braucht mindestens zwei Modelle".to_string(),
+            ));
+        }
+
+        for member in self.members.iter() {
+            if Ensemble::find(&member.model).is_some() {
+                return Err(FofError::Failed(format!(
+                    "{} ist selbst ein Ensemble",
+                    member.model
+                )));
+            }
+
+            let dataset_type = image_data_wrapper::get_dataset_type(&member.model)?;
+            if dataset_type.name != self.dataset_type {
+                return Err(FofError::Failed(format!(
+                    "{} ist {}, nicht {}",
+                    member.model, dataset_type.name, self.dataset_type
+                )));
+            }
+        }
+
+        let mut ensembles = Ensemble::load_all();
+        ensembles.push(self.clone());
+        Ensemble::save_all(&ensembles)
+    }
+
+    pub fn delete(name: &str) -> Result<(), FofError> {
+        let mut ensembles = Ensemble::load_all();
+        let before = ensembles.len();
+        ensembles.retain(|e| e.name != name);
+
+        if ensembles.len() == before {
+            return Err(FofError::ModelNotFound(name.to_string()));
+        }
+        Ensemble::save_all(&ensembles)
+    }
+
+    /// `use_cache` wird an die Mitglieder weitergegeben.
+    pub fn predict<P>(&self, image_path: &P, use_cache: bool) -> Result<Vec<Building>, FofError>
+    where
+        P: AsRef<Path> + Debug + Display,
+    {
+        let mut predictions = vec![];
+        for member in self.members.iter() {
+            let buildings = image_data_wrapper::predict(&member.model, image_path, use_cache)?;
+            predictions.push((buildings, member.weight));
+        }
+
+        Ok(filter_buildings::weighted_box_fusion(
+            &predictions,
+            self.iou_threshold,
+        ))
+    }
+
+    /// Für die Modell-Listen in der UI. Rating ist der gewichtete Durchschnitt der Mitglieder.
+    pub fn as_model(&self, members: &[Model]) -> Model {
+        let mut rating = 0.0;
+        let mut weights = 0.0;
+        for member in self.members.iter() {
+            if let Some(model) = members.iter().find(|m| m.name == member.model) {
+                rating += model.rating * member.weight as f64;
+                weights += member.weight as f64;
+            }
+        }
+
+        Model {
+            name: self.name.clone(),
+            rating: if weights > 0.0 { rating / weights } else { 0.0 },
+            dataset_type: DatasetType::by_name(&self.dataset_type).ok(),
+            metadata: ModelMetadata {
+                base_model: "Ensemble".to_string(),
+                dataset_type: self.dataset_type.clone(),
+                notes: self
+                    .members
+                    .iter()
+                    .map(|m| format!("{} x{:.2}", m.model, m.weight))
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                ..Default::default()
+            },
+            status: ModelStatus::Ensemble,
+            status_reason: None,
+        }
+    }
+}
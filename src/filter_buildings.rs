@@ -2,7 +2,7 @@ use std::usize;
 
 use crate::{image_data_wrapper::Building, prelude::*};
 
-fn get_similarity(bbox1: (f32, f32, f32, f32), bbox2: (f32, f32, f32, f32)) -> f32 {
+pub fn get_similarity(bbox1: (f32, f32, f32, f32), bbox2: (f32, f32, f32, f32)) -> f32 {
     let (x1_min, y1_min, x1_max, y1_max) = bbox1;
     let (x2_min, y2_min, x2_max, y2_max) = bbox2;
 
@@ -32,6 +32,220 @@ fn average_bbox(bbox1: (f32, f32, f32, f32), bbox2: (f32, f32, f32, f32)) -> (f3
     )
 }
 
+fn intersection(a: (f32, f32, f32, f32), b: (f32, f32, f32, f32)) -> Option<(f32, f32, f32, f32)> {
+    let rect = (a.0.max(b.0), a.1.max(b.1), a.2.min(b.2), a.3.min(b.3));
+    (rect.0 < rect.2 && rect.1 < rect.3).then_some(rect)
+}
+
+/// Fasst Boxen zusammen, die in zwei überlappenden Kacheln doppelt erkannt wurden.
+/// `buildings` sind (Box, Index der Kachel), `tiles` die Kacheln als (x1, y1, x2, y2) im ganzen Bild.
+/// Zusammengefasst werden nur Boxen aus verschiedenen Kacheln, die beide im Überlappungsstreifen
+/// der zwei Kacheln liegen, mit gleicher Klasse und IoU >= `merge_iou`. Die Box mit der höheren
+/// Confidence bleibt, Nachbarn aus der gleichen Kachel bleiben also getrennt.
+pub fn merge_tile_duplicates(
+    mut buildings: Vec<(Building, usize)>,
+    tiles: &[(f32, f32, f32, f32)],
+    merge_iou: f32,
+) -> Vec<Building> {
+    buildings.sort_by(|a, b| {
+        b.0.confidence
+            .partial_cmp(&a.0.confidence)
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+
+    let in_overlap = |bbox: (f32, f32, f32, f32), a: usize, b: usize| {
+        let (Some(tile_a), Some(tile_b)) = (tiles.get(a), tiles.get(b)) else {
+            return false;
+        };
+        intersection(*tile_a, *tile_b)
+            .and_then(|strip| intersection(strip, bbox))
+            .is_some()
+    };
+
+    // (behaltene Box, ihre Kachel, Kacheln die schon eine Box beigesteuert haben)
+    let mut result: Vec<(Building, usize, Vec<usize>)> = Vec::new();
+
+    for (building, tile) in buildings {
+        let duplicate = result.iter_mut().find(|(kept, kept_tile, merged)| {
+            !merged.contains(&tile)
+                && kept.class_id == building.class_id
+                && in_overlap(kept.bounding_box, *kept_tile, tile)
+                && in_overlap(building.bounding_box, *kept_tile, tile)
+                && get_similarity(kept.bounding_box, building.bounding_box) >= merge_iou
+        });
+
+        match duplicate {
+            Some((_, _, merged)) => merged.push(tile),
+            None => result.push((building, tile, vec![tile])),
+        }
+    }
+
+    result
+        .into_iter()
+        .map(|(building, _, _)| building)
+        .collect()
+}
+
+// Soft-NMS: darunter gilt eine Box als unterdrückt und fliegt raus
+const SOFT_NMS_MIN_SCORE: f32 = 0.001;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumIter)]
+pub enum NmsMode {
+    Off,
+    ClassAware,    // nur Boxen der gleichen Klasse unterdrücken sich
+    ClassAgnostic, // jede Box unterdrückt jede, z. B. wenn zwei Klassen um das gleiche Gebäude streiten
+    Soft,          // pro Klasse, senkt die Confidence statt zu löschen
+}
+
+impl ToString for NmsMode {
+    fn to_string(&self) -> String {
+        match self {
+            NmsMode::Off => "Aus".to_string(),
+            NmsMode::ClassAware => "Pro Klasse".to_string(),
+            NmsMode::ClassAgnostic => "Klassenübergreifend".to_string(),
+            NmsMode::Soft => "Soft-NMS".to_string(),
+        }
+    }
+}
+
+fn sort_by_confidence(buildings: &mut [Building]) {
+    buildings.sort_by(|a, b| {
+        b.confidence
+            .partial_cmp(&a.confidence)
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+}
+
+/// Non-maximum suppression auf den Rohdaten von Python (die kommen mit `conf=0.0` und ohne Limit).
+/// Bei `Soft` wird die Confidence von überlappenden Boxen mit `1 - IoU` gesenkt,
+/// danach sollte man nochmal nach `min_confidence` filtern.
+pub fn nms(buildings: &[Building], mode: NmsMode, iou_threshold: f32) -> Vec<Building> {
+    let mut remaining = buildings.to_vec();
+    sort_by_confidence(&mut remaining);
+
+    match mode {
+        NmsMode::Off => remaining,
+        NmsMode::ClassAware | NmsMode::ClassAgnostic => {
+            let mut kept: Vec<Building> = Vec::new();
+            for building in remaining {
+                let suppressed = kept.iter().any(|k| {
+                    (mode == NmsMode::ClassAgnostic || k.class_id == building.class_id)
+                        && get_similarity(k.bounding_box, building.bounding_box) > iou_threshold
+                });
+                if !suppressed {
+                    kept.push(building);
+                }
+            }
+            kept
+        }
+        NmsMode::Soft => {
+            // Die beste Box wird per Index gesucht statt nach jedem Schritt neu zu sortieren,
+            // so bleibt es bei O(n²) auch mit den tausenden Rohboxen von Python
+            let mut kept: Vec<Building> = Vec::new();
+            while let Some(best) = index_of_best(&remaining) {
+                let best = remaining.remove(best);
+
+                for other in remaining.iter_mut() {
+                    if other.class_id != best.class_id {
+                        continue;
+                    }
+                    let iou = get_similarity(best.bounding_box, other.bounding_box);
+                    if iou > iou_threshold {
+                        other.confidence *= 1.0 - iou;
+                    }
+                }
+
+                remaining.retain(|b| b.confidence >= SOFT_NMS_MIN_SCORE);
+                kept.push(best);
+            }
+            kept
+        }
+    }
+}
+
+// Bei gleicher Confidence gewinnt die vordere Box, wie beim stabilen Sortieren
+fn index_of_best(buildings: &[Building]) -> Option<usize> {
+    let mut best: Option<usize> = None;
+    for (i, b) in buildings.iter().enumerate() {
+        if best.is_none_or(|j| b.confidence > buildings[j].confidence) {
+            best = Some(i);
+        }
+    }
+    best
+}
+
+/// Weighted Box Fusion über die Vorhersagen von mehreren Modellen, `predictions` ist (Boxen, Gewicht) pro Modell.
+/// Boxen der gleichen Klasse mit IoU > `iou_threshold` zur fusionierten Box landen im gleichen Cluster,
+/// die Koordinaten werden mit `confidence * gewicht` gemittelt. Die Confidence ist die Summe der besten
+/// Box pro Modell geteilt durch die Summe aller Gewichte, eine Box die nur ein Modell sieht wird also schwächer.
+/// Mehrere Boxen vom gleichen Modell im Cluster zählen dabei nur einmal.
+pub fn weighted_box_fusion(
+    predictions: &[(Vec<Building>, f32)],
+    iou_threshold: f32,
+) -> Vec<Building> {
+    let total_weight: f32 = predictions.iter().map(|(_, w)| w.max(0.0)).sum();
+    if total_weight <= 0.0 {
+        return vec![];
+    }
+
+    // (Box, Score, Index vom Modell)
+    let mut boxes: Vec<(Building, f32, usize)> = predictions
+        .iter()
+        .enumerate()
+        .filter(|(_, (_, w))| *w > 0.0)
+        .flat_map(|(model, (buildings, w))| {
+            buildings
+                .iter()
+                .map(move |b| (b.clone(), b.confidence * w, model))
+        })
+        .collect();
+    boxes.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+
+    // (fusionierte Box, alle Boxen im Cluster mit ihrem Score und Modell)
+    let mut clusters: Vec<(Building, Vec<(Building, f32, usize)>)> = Vec::new();
+
+    for (building, score, model) in boxes {
+        let cluster = clusters.iter_mut().find(|(fused, _)| {
+            fused.class_id == building.class_id
+                && get_similarity(fused.bounding_box, building.bounding_box) > iou_threshold
+        });
+
+        let (fused, members) = match cluster {
+            Some(cluster) => cluster,
+            None => {
+                clusters.push((building.clone(), vec![]));
+                clusters.last_mut().unwrap()
+            }
+        };
+        members.push((building, score, model));
+
+        let score_sum: f32 = members.iter().map(|(_, s, _)| s).sum();
+        let weighted = |coord: fn(&Building) -> f32| {
+            members.iter().map(|(b, s, _)| coord(b) * s).sum::<f32>() / score_sum.max(f32::EPSILON)
+        };
+
+        // Boxen kommen sortiert rein, die erste pro Modell ist also seine beste
+        let mut seen = HashSet::new();
+        let best_per_model: f32 = members
+            .iter()
+            .filter(|(_, _, m)| seen.insert(*m))
+            .map(|(_, s, _)| s)
+            .sum();
+
+        fused.bounding_box = (
+            weighted(|b| b.bounding_box.0),
+            weighted(|b| b.bounding_box.1),
+            weighted(|b| b.bounding_box.2),
+            weighted(|b| b.bounding_box.3),
+        );
+        fused.confidence = (best_per_model / total_weight).min(1.0);
+    }
+
+    let mut result: Vec<Building> = clusters.into_iter().map(|(fused, _)| fused).collect();
+    sort_by_confidence(&mut result);
+    result
+}
+
 pub fn connect_level_and_buildings(
     buildings: &Vec<Building>,
     level: &Vec<Building>,
@@ -154,3 +368,184 @@ pub fn apply_filter(
 
     result
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn building(class_id: i32, confidence: f32, bbox: (f32, f32, f32, f32)) -> Building {
+        Building {
+            class_id,
+            class_name: format!("klasse_{}", class_id),
+            confidence,
+            bounding_box: bbox,
+        }
+    }
+
+    fn assert_close(a: f32, b: f32) {
+        assert!((a - b).abs() < 1e-5, "{} != {}", a, b);
+    }
+
+    #[test]
+    fn nms_off_keeps_everything_sorted() {
+        let boxes = vec![
+            building(0, 0.3, (0.0, 0.0, 10.0, 10.0)),
+            building(0, 0.9, (0.0, 0.0, 10.0, 10.0)),
+        ];
+        let result = nms(&boxes, NmsMode::Off, 0.5);
+        assert_eq!(result.len(), 2);
+        assert_close(result[0].confidence, 0.9);
+    }
+
+    #[test]
+    fn nms_class_aware_only_suppresses_same_class() {
+        let boxes = vec![
+            building(0, 0.9, (0.0, 0.0, 10.0, 10.0)),
+            building(0, 0.8, (1.0, 0.0, 11.0, 10.0)),
+            building(1, 0.7, (0.0, 0.0, 10.0, 10.0)),
+        ];
+        let result = nms(&boxes, NmsMode::ClassAware, 0.5);
+        assert_eq!(result.len(), 2);
+        assert_eq!(result[0].class_id, 0);
+        assert_close(result[0].confidence, 0.9);
+        assert_eq!(result[1].class_id, 1);
+    }
+
+    #[test]
+    fn nms_class_agnostic_suppresses_across_classes() {
+        let boxes = vec![
+            building(1, 0.7, (0.0, 0.0, 10.0, 10.0)),
+            building(0, 0.9, (0.0, 0.0, 10.0, 10.0)),
+            building(0, 0.5, (50.0, 50.0, 60.0, 60.0)),
+        ];
+        let result = nms(&boxes, NmsMode::ClassAgnostic, 0.5);
+        assert_eq!(result.len(), 2);
+        assert_eq!(result[0].class_id, 0);
+        assert_close(result[0].confidence, 0.9);
+        assert_close(result[1].confidence, 0.5);
+    }
+
+    #[test]
+    fn soft_nms_decays_instead_of_removing() {
+        // IoU = 90 / 110
+        let boxes = vec![
+            building(0, 0.9, (0.0, 0.0, 10.0, 10.0)),
+            building(0, 0.8, (1.0, 0.0, 11.0, 10.0)),
+            building(1, 0.6, (0.0, 0.0, 10.0, 10.0)),
+        ];
+        let result = nms(&boxes, NmsMode::Soft, 0.5);
+        assert_eq!(result.len(), 3);
+        assert_close(result[0].confidence, 0.9);
+        assert_close(result[1].confidence, 0.6);
+        assert_close(result[2].confidence, 0.8 * (1.0 - 90.0 / 110.0));
+    }
+
+    #[test]
+    fn soft_nms_drops_exact_duplicates() {
+        let boxes = vec![
+            building(0, 0.9, (0.0, 0.0, 10.0, 10.0)),
+            building(0, 0.8, (0.0, 0.0, 10.0, 10.0)),
+        ];
+        let result = nms(&boxes, NmsMode::Soft, 0.5);
+        assert_eq!(result.len(), 1);
+        assert_close(result[0].confidence, 0.9);
+    }
+
+    #[test]
+    fn wbf_averages_confidence_over_all_models() {
+        let predictions = vec![
+            (vec![building(0, 0.8, (0.0, 0.0, 10.0, 10.0))], 1.0),
+            (vec![building(0, 0.6, (0.0, 0.0, 10.0, 10.0))], 1.0),
+        ];
+        let result = weighted_box_fusion(&predictions, 0.5);
+        assert_eq!(result.len(), 1);
+        assert_close(result[0].confidence, 0.7);
+        assert_close(result[0].bounding_box.2, 10.0);
+    }
+
+    #[test]
+    fn wbf_weakens_boxes_only_one_model_sees() {
+        let predictions = vec![
+            (vec![building(0, 0.8, (0.0, 0.0, 10.0, 10.0))], 1.0),
+            (vec![], 1.0),
+        ];
+        let result = weighted_box_fusion(&predictions, 0.5);
+        assert_eq!(result.len(), 1);
+        assert_close(result[0].confidence, 0.4);
+    }
+
+    #[test]
+    fn wbf_counts_each_model_once_per_cluster() {
+        let predictions = vec![
+            (
+                vec![
+                    building(0, 0.8, (0.0, 0.0, 10.0, 10.0)),
+                    building(0, 0.6, (0.0, 0.0, 10.0, 10.0)),
+                ],
+                1.0,
+            ),
+            (vec![], 1.0),
+        ];
+        let result = weighted_box_fusion(&predictions, 0.5);
+        assert_eq!(result.len(), 1);
+        assert_close(result[0].confidence, 0.4);
+    }
+
+    #[test]
+    fn wbf_weights_coordinates() {
+        let predictions = vec![
+            (vec![building(0, 1.0, (0.0, 0.0, 10.0, 10.0))], 3.0),
+            (vec![building(0, 1.0, (1.0, 1.0, 11.0, 11.0))], 1.0),
+        ];
+        let result = weighted_box_fusion(&predictions, 0.5);
+        assert_eq!(result.len(), 1);
+        assert_close(result[0].bounding_box.0, 0.25);
+        assert_close(result[0].bounding_box.3, 10.25);
+        assert_close(result[0].confidence, 1.0);
+    }
+
+    // Zwei Kacheln, die sich bei x = 80..100 überlappen
+    const TILES: [(f32, f32, f32, f32); 2] = [(0.0, 0.0, 100.0, 100.0), (80.0, 0.0, 180.0, 100.0)];
+
+    #[test]
+    fn tile_merge_keeps_the_stronger_box() {
+        let boxes = vec![
+            (building(0, 0.6, (86.0, 10.0, 96.0, 20.0)), 1),
+            (building(0, 0.9, (85.0, 10.0, 95.0, 20.0)), 0),
+        ];
+        let result = merge_tile_duplicates(boxes, &TILES, 0.5);
+        assert_eq!(result.len(), 1);
+        assert_close(result[0].confidence, 0.9);
+        assert_eq!(result[0].bounding_box, (85.0, 10.0, 95.0, 20.0));
+    }
+
+    #[test]
+    fn tile_merge_keeps_neighbours_from_the_same_tile() {
+        let boxes = vec![
+            (building(0, 0.9, (85.0, 10.0, 95.0, 20.0)), 0),
+            (building(0, 0.8, (86.0, 10.0, 96.0, 20.0)), 0),
+        ];
+        assert_eq!(merge_tile_duplicates(boxes, &TILES, 0.5).len(), 2);
+    }
+
+    #[test]
+    fn tile_merge_takes_one_box_per_tile() {
+        let boxes = vec![
+            (building(0, 0.9, (85.0, 10.0, 95.0, 20.0)), 0),
+            (building(0, 0.8, (86.0, 10.0, 96.0, 20.0)), 1),
+            (building(0, 0.7, (85.0, 11.0, 95.0, 21.0)), 1),
+        ];
+        assert_eq!(merge_tile_duplicates(boxes, &TILES, 0.5).len(), 2);
+    }
+
+    #[test]
+    fn tile_merge_ignores_other_classes_and_boxes_outside_the_strip() {
+        let boxes = vec![
+            (building(0, 0.9, (85.0, 10.0, 95.0, 20.0)), 0),
+            (building(1, 0.8, (85.0, 10.0, 95.0, 20.0)), 1),
+            (building(0, 0.9, (40.0, 10.0, 60.0, 20.0)), 0),
+            (building(0, 0.8, (40.0, 10.0, 60.0, 20.0)), 1),
+        ];
+        assert_eq!(merge_tile_duplicates(boxes, &TILES, 0.5).len(), 4);
+    }
+}
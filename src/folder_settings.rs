@@ -0,0 +1,33 @@
+use crate::prelude::*;
+
+pub const FOLDER_SETTINGS_FILE: &str = "folder_settings.yaml";
+
+/// Ordner, die in den Einstellungen gewählt wurden. Leere Felder = Umgebungsvariable / Standard.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FolderSettings {
+    pub communication_dir: Option<PathBuf>,
+    pub base_weights_dir: Option<PathBuf>,
+}
+
+impl FolderSettings {
+    pub fn load() -> FolderSettings {
+        fs::read_to_string(FOLDER_SETTINGS_FILE)
+            .ok()
+            .and_then(|s| serde_yaml::from_str(&s).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) -> Result<(), FofError> {
+        let yaml =
+            serde_yaml::to_string(self).map_err(|e| FofError::YamlParseError(e.to_string()))?;
+        fs::write(FOLDER_SETTINGS_FILE, yaml)?;
+        Ok(())
+    }
+
+    pub fn update(f: impl FnOnce(&mut FolderSettings)) -> Result<(), FofError> {
+        let mut settings = FolderSettings::load();
+        f(&mut settings);
+        settings.save()
+    }
+}
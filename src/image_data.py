@@ -1,102 +1,176 @@
 import json
 import os
+import sys
 from ultralytics import YOLO
 import argparse
+import yaml
+import shutil
+import torch
 
-def write_data(data, model_name):
-    data_path = f"Communication/{model_name}/data.json"
+def write_data(data, model_name, data_path=None):
+    if data_path is None:
+        data_path = f"Communication/{model_name}/data.json"
     os.makedirs(os.path.dirname(data_path), exist_ok=True)
 
     with open(data_path, 'w', encoding='utf-8') as f:
         json.dump(data, f, indent=4)
 
-def create_new_model(model_name, data_set_type, yolo_model):
+def create_new_model(model_name, data_yaml, yolo_model, epochs=1, imgsz=640):
     model = YOLO(yolo_model)
-    if data_set_type == "buildings":
-        DATA_YAML = "dataset_buildings/data.yaml"
-    else:
-        DATA_YAML = "dataset_level/data.yaml"
-    model.train(data=DATA_YAML, epochs=1, name=model_name)
+    model.train(data=data_yaml, epochs=epochs, imgsz=imgsz, project="runs/detect", name=model_name)
 
-def train_model(model_name, data_set_type, epochen):
+def check_weights(weights_path):
+    # Wird vor --create-model aufgerufen, damit kaputte Dateien keinen halben Run anlegen
+    model = YOLO(weights_path)
+    if model.task != "detect":
+        raise ValueError(f"{weights_path} ist ein '{model.task}' Modell, gebraucht wird 'detect'")
+    print(f"{weights_path}: {len(model.names)} Klassen")
+
+# Standardwerte, falls keine Config übergeben wird (gleich wie TrainingConfig::default() in Rust)
+DEFAULT_TRAINING_CONFIG = {
+    "device": "cuda:0",
+    "imgsz": 640,
+    "batch": 4,                # 16 passt meist auf 8GB VRAM (zur Not 8)
+    "workers": 4,              # CPU besser nutzen
+    "optimizer": "SGD",        # stabiler als AdamW bei YOLO
+    "momentum": 0.937,         # Standard-Optimalwert
+    "lr0": 0.01,               # höherer Start-LR für SGD
+    "lrf": 0.01,               # OneCycleLR fährt runter
+    "weight_decay": 0.0005,
+    "patience": None,          # None = 30% der Epochen
+    "warmup_epochs": 3,        # 2–5 ideal
+    "warmup_momentum": 0.8,
+    "warmup_bias_lr": 0.1,
+    "amp": True,               # Mixed Precision
+    "cos_lr": True,            # Cosine / OneCycle Scheduler
+    "cache": "ram",
+
+    # Augmentation (deine Spezialsettings, nicht geändert):
+    "hsv_h": 0.0,
+    "hsv_s": 0.0,
+    "hsv_v": 0.0,
+    "degrees": 0.0,
+    "translate": 0.05,
+    "scale": 0.9,
+    "shear": 0.0,
+    "perspective": 0.0,
+    "flipud": 0.0,
+    "fliplr": 0.2,
+    "mosaic": 0.5,
+    "mixup": 0.0,
+    "copy_paste": 0.0,
+}
+
+def load_training_config(config_path, epochen):
+    config = dict(DEFAULT_TRAINING_CONFIG)
+    if config_path is not None and os.path.exists(config_path):
+        with open(config_path, 'r', encoding='utf-8') as f:
+            config.update(yaml.safe_load(f) or {})
+
+    if config["patience"] is None:
+        config["patience"] = round(epochen*0.3)
+    if config["cache"] == "none":
+        config["cache"] = False
+
+    return config
+
+def stop_file(model_name):
+    # Wird von Rust angelegt, wenn das Training nach der aktuellen Epoche aufhören soll
+    return f"runs/detect/{model_name}/stop_requested"
+
+def resume_file(model_name):
+    return f"runs/detect/{model_name}/weights/resume.pt"
+
+def add_stop_callback(model, model_name):
+    if os.path.exists(stop_file(model_name)):
+        os.remove(stop_file(model_name))
+
+    def on_fit_epoch_end(trainer):
+        if not os.path.exists(stop_file(model_name)):
+            return
+        # last.pt ist an dieser Stelle schon gespeichert und hat noch den Optimizer.
+        # Beim normalen Ende wird der entfernt, deshalb eine Kopie zum Fortsetzen.
+        shutil.copy(trainer.last, resume_file(model_name))
+        print(f"Stop angefordert, Training endet nach Epoche {trainer.epoch + 1}")
+        trainer.stop = True
+
+    model.add_callback("on_fit_epoch_end", on_fit_epoch_end)
+
+def stop_requested(model_name):
+    requested = os.path.exists(stop_file(model_name))
+    if requested:
+        os.remove(stop_file(model_name))
+    return requested
+
+def train_model(model_name, data_yaml, epochen, config_path=None):
     model_path = f"runs/detect/{model_name}/weights/best.pt"
     model = YOLO(model_path)
-    if data_set_type == "buildings":
-        DATA_YAML = "dataset_buildings/data.yaml"
-    else:
-        DATA_YAML = "dataset_level/data.yaml"
 
+    # resume.pt von einem älteren Lauf würde sonst beim Fortsetzen diesem Lauf vorgezogen
+    if os.path.exists(resume_file(model_name)):
+        os.remove(resume_file(model_name))
 
+    config = load_training_config(config_path, epochen)
+
+    add_stop_callback(model, model_name)
     model.train(
-    data=DATA_YAML,
+    data=data_yaml,
     epochs=epochen,
-    imgsz=640,
-    batch=4,                  # 16 passt meist auf 8GB VRAM (zur Not 8)
-    workers=4,                 # CPU besser nutzen
-    optimizer="SGD",           # stabiler als AdamW bei YOLO
-    momentum=0.937,            # Standard-Optimalwert
-    lr0=0.01,                  # höherer Start-LR für SGD
-    lrf=0.01,                  # OneCycleLR fährt runter
-    weight_decay=0.0005,
-    patience=round(epochen*0.3),
-    warmup_epochs=3,           # 2–5 ideal
-    warmup_momentum=0.8,
-    warmup_bias_lr=0.1,
     pretrained=True,
-    amp=True,                  # Mixed Precision
-    device='cuda:0',
-
-    # Augmentation (deine Spezialsettings, nicht geändert):
-    hsv_h=0.0,
-    hsv_s=0.0,
-    hsv_v=0.0,
-    degrees=0.0,
-    translate=0.05,
-    scale=0.9,
-    shear=0.0,
-    perspective=0.0,
-    flipud=0.0,
-    fliplr=0.2,
-    mosaic=0.5,
-    mixup=0.0,
-    copy_paste=0.0,
-
     save_period=0,
     exist_ok=True,
     val=True,
     project="runs/detect",
     name=model_name,
-    cache="ram",
-    cos_lr=True                # Cosine / OneCycle Scheduler
+    **config
     )
 
+    if stop_requested(model_name):
+        return
 
-    model.val(
-    data=DATA_YAML,
-    conf=0.3,
-    imgsz=960,
-    iou= 0.5,
-    visualize= True,
-    save=True,
-    save_txt=True,
-    save_conf=True,
-    project="testvals",
-    name=f"val_run_{model_name}",
-    batch=16,
-    plots=True,
-    verbose=True,
-    )
+    validate_after_training(model, model_name, data_yaml)
+
+def resume_training(model_name, data_yaml):
+    # Nach einem Stop über die Datei gibt es resume.pt, nach SIGINT / Absturz ist last.pt noch fortsetzbar.
+    # Der neuere gewinnt, damit nie auf einen älteren Stand zurückgesprungen wird.
+    candidates = [resume_file(model_name), f"runs/detect/{model_name}/weights/last.pt"]
+    candidates = [c for c in candidates if os.path.exists(c)]
+    if not candidates:
+        raise RuntimeError(f"Kein Checkpoint zum Fortsetzen für {model_name}")
+    checkpoint = max(candidates, key=os.path.getmtime)
+
+    ckpt = torch.load(checkpoint, map_location="cpu", weights_only=False)
+    if ckpt.get("epoch", -1) == -1 or ckpt.get("optimizer") is None:
+        raise RuntimeError(f"{checkpoint} ist ein fertiges Training, da gibt es nichts fortzusetzen")
 
-def testvals(model_name,data_set_type):
+    model = YOLO(checkpoint)
+    add_stop_callback(model, model_name)
+    model.train(resume=True)
+
+    if stop_requested(model_name):
+        return
+
+    if os.path.exists(resume_file(model_name)):
+        os.remove(resume_file(model_name))
+
+    validate_after_training(model, model_name, data_yaml)
+
+def validate_after_training(model, model_name, data_yaml):
+    run_validation(model, model_name, data_yaml)
+
+def testvals(model_name, data_yaml):
     model_path = f"runs/detect/{model_name}/weights/best.pt"
-    if data_set_type == "buildings":
-        DATA_YAML = "dataset_buildings/data.yaml"
-    else:
-        DATA_YAML = "dataset_level/data.yaml"
 
     model = YOLO(model_path)
+    run_validation(model, model_name, data_yaml)
+
+def run_validation(model, model_name, data_yaml):
+    # model.val() gibt nur die Metriken zurück, die Zählungen hat der Validator selbst
+    validators = []
+    model.add_callback("on_val_end", lambda validator: validators.append(validator))
+
     model.val(
-    data=DATA_YAML,
+    data=data_yaml,
     conf=0.3,
     imgsz=960,
     iou= 0.5,
@@ -106,19 +180,36 @@ def testvals(model_name,data_set_type):
     save_conf=True,
     project="testvals",
     name=f"val_run_{model_name}",
+    exist_ok=True,
     batch=16,
     plots=True,
     verbose=True,
     )
 
+    if not validators:
+        raise RuntimeError("Validierung hat keinen Validator geliefert (on_val_end nicht aufgerufen)")
+    write_class_metrics(validators[-1], model.names, f"testvals/val_run_{model_name}/class_metrics.csv")
 
+def write_class_metrics(validator, class_names, csv_path):
+    # Gleiche Zahlen wie die Tabelle, die ultralytics mit verbose=True ausgibt
+    metrics = validator.metrics
+    images = getattr(validator, "nt_per_image", None)
+    instances = getattr(validator, "nt_per_class", None)
+    if images is None or instances is None:
+        raise RuntimeError("Validator hat keine nt_per_image/nt_per_class, ultralytics-Version passt nicht")
 
-def write_prediction_to_json(model_name):
-    image_path = f"Communication/{model_name}/screenshot.png"
-    model_path = f"runs/detect/{model_name}/weights/best.pt"
-    model = YOLO(model_path)
-    results = model.predict(source=image_path, max_det= 999999999, conf=0.0)[0]
-    class_names = model.names  # z. B. {0: "cannon", 1: "elixir", ...}
+    os.makedirs(os.path.dirname(csv_path), exist_ok=True)
+    with open(csv_path, 'w', encoding='utf-8') as f:
+        f.write("class,images,instances,precision,recall,mAP50,mAP50-95\n")
+        for i, c in enumerate(metrics.ap_class_index):
+            p, r, map50, map50_95 = metrics.class_result(i)
+            f.write(f"{class_names[c]},{int(images[c])},{int(instances[c])},{p:.5f},{r:.5f},{map50:.5f},{map50_95:.5f}\n")
+
+
+
+def predict_buildings(model, image_path):
+    results = model.predict(source=image_path, max_det= 999999999, conf=0.0, verbose=False)[0]
+    class_names = model.names  # z. B. {0: "cannon", 1: "elixir", ...}
     output = []
     for box in results.boxes:
         cls_id = int(box.cls[0].item())              # class index (int)
@@ -133,7 +224,65 @@ def write_prediction_to_json(model_name):
             "bounding_box": (xyxy[0], xyxy[1], xyxy[2], xyxy[3])
         })
 
-    write_data(output, model_name)
+    return output
+
+def write_prediction_to_json(model_name, image_path=None, data_path=None):
+    if image_path is None:
+        image_path = f"Communication/{model_name}/screenshot.png"
+    model_path = f"runs/detect/{model_name}/weights/best.pt"
+    model = YOLO(model_path)
+    output = predict_buildings(model, image_path)
+
+    write_data(output, model_name, data_path)
+
+def export_onnx(model_name):
+    model_path = f"runs/detect/{model_name}/weights/best.pt"
+    model = YOLO(model_path)
+    # landet als best.onnx neben best.pt
+    model.export(format="onnx", imgsz=640, opset=12)
+
+def respond(response):
+    # Eine Antwort pro Zeile, damit Rust sie mit read_line lesen kann
+    sys.stdout.write(json.dumps(response) + "\n")
+    sys.stdout.flush()
+
+def serve(model_name):
+    # Bleibt am Leben und beantwortet Anfragen zeilenweise als JSON über stdin/stdout,
+    # damit das Modell nur einmal geladen werden muss.
+    model_path = f"runs/detect/{model_name}/weights/best.pt"
+    model = YOLO(model_path)
+    respond({"id": 0, "ok": True, "ready": True})
+
+    for line in sys.stdin:
+        line = line.strip()
+        if not line:
+            continue
+
+        request_id = None
+        try:
+            request = json.loads(line)
+            request_id = request.get("id")
+            cmd = request.get("cmd")
+
+            if cmd == "ping":
+                respond({"id": request_id, "ok": True})
+            elif cmd == "reload":
+                model = YOLO(model_path)
+                respond({"id": request_id, "ok": True})
+            elif cmd == "predict":
+                output = predict_buildings(model, request["image"])
+                data_path = request["output"]
+                os.makedirs(os.path.dirname(data_path), exist_ok=True)
+                with open(data_path, 'w', encoding='utf-8') as f:
+                    json.dump(output, f, indent=4)
+                respond({"id": request_id, "ok": True})
+            elif cmd == "shutdown":
+                respond({"id": request_id, "ok": True})
+                break
+            else:
+                respond({"id": request_id, "ok": False, "error": f"Unbekannter Befehl: {cmd}"})
+        except Exception as e:
+            respond({"id": request_id, "ok": False, "error": str(e)})
 
 parser = argparse.ArgumentParser(description="Trainings- und Vorhersagemodus für YOLO Modell")
 
@@ -141,30 +290,52 @@ parser.add_argument('--zahl_erkennen', action='store_true', help='zahl erkennen'
 parser.add_argument('--path', type=str, default=None, help='path zum image')
 parser.add_argument('--create-model', action='store_true', help='Erstelle ein neues Modell mit einem bestimmten Namen')
 parser.add_argument('--testvals', action='store_true', help='testvals')
+parser.add_argument('--check-weights', action='store_true', help='Prüfe, ob sich --base laden lässt')
 parser.add_argument('--train', action='store_true', help='Starte ein neues Training')
+parser.add_argument('--resume', action='store_true', help='Setze ein abgebrochenes Training fort (mit --train)')
 parser.add_argument('--predict', action='store_true', help='Mache eine Vorhersage mit dem Modell')
+parser.add_argument('--export-onnx', action='store_true', help='Exportiere best.pt als best.onnx')
+parser.add_argument('--serve', action='store_true', help='Starte einen Prozess, der Vorhersagen über stdin/stdout beantwortet')
 parser.add_argument('--model-name', type=str, default=None, help='Name des Modells / Verzeichnisses')
 parser.add_argument('--epochs', type=int, default=None, help='Anzahl der Trainings-Epochen')
 parser.add_argument('--base', type=str, default=None, help='YOLO-Modellbasis (z. B. yolov8n.pt, yolov8s.pt)')
-parser.add_argument('--dataset_type', type=str, default=None, help='')
+parser.add_argument('--dataset_type', type=str, default=None, help='Name vom Datensatz, benutzt dataset_<name>/data.yaml')
+parser.add_argument('--data', type=str, default=None, help='Pfad zur data.yaml (statt --dataset_type)')
+parser.add_argument('--imgsz', type=int, default=640, help='Bildgröße fürs erste Training bei --create-model')
+parser.add_argument('--config', type=str, default=None, help='YAML mit den Trainings-Parametern (TrainingConfig)')
+parser.add_argument('--output', type=str, default=None, help='Wohin die data.json bei --predict geschrieben wird')
 
 
 args = parser.parse_args()
 
 epochs = args.epochs
+data_yaml = args.data or f"dataset_{args.dataset_type}/data.yaml"
 
 if args.testvals:
-    testvals(args.model_name, args.dataset_type)
+    testvals(args.model_name, data_yaml)
+
+
+if args.check_weights:
+    check_weights(args.base)
 
 
 if args.create_model:
-    create_new_model(args.model_name, args.dataset_type, args.base)
+    create_new_model(args.model_name, data_yaml, args.base, epochs or 1, args.imgsz)
 
 
-if args.train:
-    train_model(args.model_name, args.dataset_type, epochs)
+if args.train and args.resume:
+    resume_training(args.model_name, data_yaml)
+elif args.train:
+    train_model(args.model_name, data_yaml, epochs, args.config)
 
 
 if args.predict:
-    write_prediction_to_json(args.model_name)
+    write_prediction_to_json(args.model_name, args.path, args.output)
+
+
+if args.export_onnx:
+    export_onnx(args.model_name)
+
 
+if args.serve:
+    serve(args.model_name)
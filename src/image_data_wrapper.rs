@@ -2,18 +2,39 @@ use std::fmt::format;
 
 use crate::prelude::*;
 
-#[derive(Debug, PartialEq, EnumIter, Eq, Clone)]
+use std::sync::RwLock;
+
+#[derive(Debug, PartialEq, Eq, Clone)]
 pub enum YoloModel {
     YOLOv8n,
     YOLOv8s,
     YOLOv8m,
     YOLOv8l,
     YOLOv8x,
+    YOLO11n,
+    YOLO11s,
+    YOLO11m,
+    YOLO11l,
+    YOLO11x,
+    Custom(PathBuf), // beliebige lokale .pt Datei
 }
-#[derive(Debug, Clone, PartialEq)]
-pub enum DatasetType {
-    Buildings,
-    Level,
+/// Ein Datensatz (`<dir>/data.yaml`). Kommt aus `DATASETS_FILE` oder aus einem `dataset_<name>` Ordner,
+/// siehe `DatasetType::all`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct DatasetType {
+    pub name: String,
+
+    /// Ordner mit `data.yaml`, leer = `dataset_<name>`
+    #[serde(default)]
+    pub dir: String,
+
+    #[serde(default)]
+    pub display_name: String,
+
+    /// Welcher Teil vom Label beim Labeln in diesen Datensatz geht (z. B. `\d+` für das Level).
+    /// `None` = der Datensatz bekommt keine Bilder aus dem Labeling Tab.
+    #[serde(default)]
+    pub label_regex: Option<String>,
 }
 impl ToString for YoloModel {
     fn to_string(&self) -> String {
@@ -33,24 +54,267 @@ impl ToString for YoloModel {
             YoloModel::YOLOv8x => {
                 return "yolov8x".to_string();
             }
+            YoloModel::YOLO11n => {
+                return "yolo11n".to_string();
+            }
+            YoloModel::YOLO11s => {
+                return "yolo11s".to_string();
+            }
+            YoloModel::YOLO11m => {
+                return "yolo11m".to_string();
+            }
+            YoloModel::YOLO11l => {
+                return "yolo11l".to_string();
+            }
+            YoloModel::YOLO11x => {
+                return "yolo11x".to_string();
+            }
+            YoloModel::Custom(path) => {
+                return path.display().to_string();
+            }
+        }
+    }
+}
+
+static BASE_WEIGHTS_DIR: RwLock<Option<PathBuf>> = RwLock::new(None);
+
+/// Ordner mit lokalen Basis-Gewichten (`*.pt`).
+/// Standard ist `base_weights`, kann mit `FOF_BASE_WEIGHTS_DIR` oder in den Einstellungen geändert werden.
+/// Der Ordner aus den Einstellungen wird mit `load_base_weights_dir` geladen.
+pub fn base_weights_dir() -> PathBuf {
+    if let Some(dir) = BASE_WEIGHTS_DIR.read().unwrap().clone() {
+        return dir;
+    }
+
+    std::env::var("FOF_BASE_WEIGHTS_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| PathBuf::from("base_weights"))
+}
+
+/// Übernimmt den gespeicherten Ordner, außer `FOF_BASE_WEIGHTS_DIR` ist gesetzt.
+pub fn load_base_weights_dir() {
+    if std::env::var("FOF_BASE_WEIGHTS_DIR").is_ok() {
+        return;
+    }
+    if let Some(path) = folder_settings::FolderSettings::load().base_weights_dir {
+        *BASE_WEIGHTS_DIR.write().unwrap() = Some(path);
+    }
+}
+
+pub fn set_base_weights_dir(path: PathBuf) -> Result<(), FofError> {
+    *BASE_WEIGHTS_DIR.write().unwrap() = Some(path.clone());
+    folder_settings::FolderSettings::update(|s| s.base_weights_dir = Some(path))
+}
+
+impl YoloModel {
+    /// Die Modelle, die ultralytics selbst herunterladen kann.
+    pub fn builtin() -> Vec<YoloModel> {
+        vec![
+            YoloModel::YOLOv8n,
+            YoloModel::YOLOv8s,
+            YoloModel::YOLOv8m,
+            YoloModel::YOLOv8l,
+            YoloModel::YOLOv8x,
+            YoloModel::YOLO11n,
+            YoloModel::YOLO11s,
+            YoloModel::YOLO11m,
+            YoloModel::YOLO11l,
+            YoloModel::YOLO11x,
+        ]
+    }
+
+    /// Eigene `.pt` Dateien aus `base_weights_dir()`. Dateien mit dem Namen
+    /// von einem eingebauten Modell (z. B. `yolov8n.pt`) tauchen hier nicht auf.
+    pub fn discover() -> Vec<YoloModel> {
+        let builtin: Vec<String> = YoloModel::builtin().iter().map(|m| m.to_string()).collect();
+
+        let Ok(entries) = fs::read_dir(base_weights_dir()) else {
+            return vec![];
+        };
+
+        let mut found: Vec<PathBuf> = entries
+            .flatten()
+            .map(|e| e.path())
+            .filter(|p| p.extension().map(|e| e == "pt").unwrap_or(false))
+            .filter(|p| {
+                let stem = p.file_stem().unwrap_or_default().to_string_lossy();
+                !builtin.contains(&stem.to_string())
+            })
+            .collect();
+        found.sort();
+
+        found.into_iter().map(YoloModel::Custom).collect()
+    }
+
+    /// Eingebaute Modelle liegen schon im Ordner und müssen nicht heruntergeladen werden.
+    pub fn is_local(&self) -> bool {
+        match self {
+            YoloModel::Custom(path) => path.exists(),
+            m => base_weights_dir()
+                .join(format!("{}.pt", m.to_string()))
+                .exists(),
+        }
+    }
+
+    /// Was an ultralytics übergeben wird: ein Pfad, wenn es die Datei lokal gibt,
+    /// sonst `<name>.pt` zum Herunterladen.
+    pub fn weights_arg(&self) -> String {
+        match self {
+            YoloModel::Custom(path) => path.display().to_string(),
+            m if m.is_local() => base_weights_dir()
+                .join(format!("{}.pt", m.to_string()))
+                .display()
+                .to_string(),
+            m => format!("{}.pt", m.to_string()),
         }
     }
 }
 
 impl ToString for DatasetType {
     fn to_string(&self) -> String {
-        match self {
-            DatasetType::Buildings => {
-                return "buildings".to_string();
+        self.name.clone()
+    }
+}
+
+pub const DATASETS_FILE: &str = "datasets.yaml";
+
+// `DatasetType::all()` wird oft gebraucht (jede Vorhersage, jedes Modell), gelesen wird nur einmal
+static DATASETS: RwLock<Option<Vec<DatasetType>>> = RwLock::new(None);
+
+impl DatasetType {
+    pub const BUILDINGS: &'static str = "buildings";
+    pub const LEVEL: &'static str = "level";
+
+    /// Die zwei Datensätze, die es immer gab. Können in `DATASETS_FILE` überschrieben werden.
+    fn builtin() -> Vec<DatasetType> {
+        vec![
+            DatasetType {
+                name: DatasetType::BUILDINGS.to_string(),
+                dir: String::new(),
+                display_name: "🏗️ Building Model".to_string(),
+                label_regex: Some(r"\D+".to_string()), // Nur Buchstaben
+            },
+            DatasetType {
+                name: DatasetType::LEVEL.to_string(),
+                dir: String::new(),
+                display_name: "🎯 Level Model".to_string(),
+                label_regex: Some(r"\d+".to_string()), // Nur Ziffern
+            },
+        ]
+    }
+
+    /// Eingebaute Datensätze, dann Einträge aus `DATASETS_FILE`, dann alle übrigen
+    /// `dataset_<name>` Ordner mit einer `data.yaml` (ohne Label-Regel).
+    /// Wird beim ersten Aufruf gelesen, nach Änderungen `reload` aufrufen.
+    pub fn all() -> Vec<DatasetType> {
+        if let Some(datasets) = DATASETS.read().unwrap().as_ref() {
+            return datasets.clone();
+        }
+
+        let datasets = DatasetType::scan();
+        *DATASETS.write().unwrap() = Some(datasets.clone());
+        datasets
+    }
+
+    /// `DATASETS_FILE` und die `dataset_<name>` Ordner beim nächsten `all` neu lesen.
+    pub fn reload() {
+        *DATASETS.write().unwrap() = None;
+    }
+
+    fn scan() -> Vec<DatasetType> {
+        let mut datasets = DatasetType::builtin();
+
+        let registered: Vec<DatasetType> = fs::read_to_string(DATASETS_FILE)
+            .ok()
+            .and_then(|s| match serde_yaml::from_str(&s) {
+                Ok(registered) => Some(registered),
+                Err(e) => {
+                    eprintln!("{} ist kaputt: {}", DATASETS_FILE, e);
+                    None
+                }
+            })
+            .unwrap_or_default();
+
+        for dataset in registered {
+            match datasets.iter_mut().find(|d| d.name == dataset.name) {
+                Some(existing) => *existing = dataset,
+                None => datasets.push(dataset),
             }
-            DatasetType::Level => {
-                return "level".to_string();
+        }
+
+        let mut discovered: Vec<DatasetType> = fs::read_dir(".")
+            .map(|entries| {
+                entries
+                    .flatten()
+                    .filter_map(|e| {
+                        let dir = e.file_name().to_string_lossy().to_string();
+                        let name = dir.strip_prefix("dataset_")?.to_string();
+                        e.path().join("data.yaml").exists().then_some(DatasetType {
+                            name,
+                            dir,
+                            display_name: String::new(),
+                            label_regex: None,
+                        })
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        discovered.sort_by(|a, b| a.name.cmp(&b.name));
+
+        for dataset in discovered {
+            if !datasets.iter().any(|d| d.name == dataset.name) {
+                datasets.push(dataset);
             }
         }
+
+        datasets
+    }
+
+    pub fn by_name(name: &str) -> Result<DatasetType, FofError> {
+        DatasetType::all()
+            .into_iter()
+            .find(|d| d.name == name)
+            .ok_or_else(|| FofError::UnsupportedDataset(name.to_string()))
+    }
+
+    /// Datensätze, die beim Speichern im Labeling Tab Bilder bekommen.
+    pub fn labeling_targets() -> Vec<DatasetType> {
+        DatasetType::all()
+            .into_iter()
+            .filter(|d| d.label_regex.is_some())
+            .collect()
+    }
+
+    pub fn dir(&self) -> String {
+        if self.dir.is_empty() {
+            format!("dataset_{}", self.name)
+        } else {
+            self.dir.clone()
+        }
+    }
+
+    pub fn data_yaml(&self) -> String {
+        format!("{}/data.yaml", self.dir())
+    }
+
+    pub fn display_name(&self) -> String {
+        if self.display_name.is_empty() {
+            self.name.clone()
+        } else {
+            self.display_name.clone()
+        }
+    }
+
+    pub fn is_buildings(&self) -> bool {
+        self.name == DatasetType::BUILDINGS
+    }
+
+    pub fn is_level(&self) -> bool {
+        self.name == DatasetType::LEVEL
     }
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Building {
     pub class_id: i32,
     pub class_name: String,
@@ -58,11 +322,393 @@ pub struct Building {
     pub bounding_box: (f32, f32, f32, f32),
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumIter, Serialize, Deserialize)]
+pub enum TrainingDevice {
+    #[serde(rename = "cuda:0")]
+    Cuda,
+    #[serde(rename = "cpu")]
+    Cpu,
+}
+
+impl ToString for TrainingDevice {
+    fn to_string(&self) -> String {
+        match self {
+            TrainingDevice::Cuda => "GPU (cuda:0)".to_string(),
+            TrainingDevice::Cpu => "CPU".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumIter, Serialize, Deserialize)]
+pub enum CacheMode {
+    #[serde(rename = "ram")]
+    Ram,
+    #[serde(rename = "disk")]
+    Disk,
+    #[serde(rename = "none")]
+    Aus,
+}
+
+impl ToString for CacheMode {
+    fn to_string(&self) -> String {
+        match self {
+            CacheMode::Ram => "RAM".to_string(),
+            CacheMode::Disk => "Disk".to_string(),
+            CacheMode::Aus => "Aus".to_string(),
+        }
+    }
+}
+
+/// Alle Parameter für `model.train(...)` in image_data.py.
+/// Wird als YAML in den Modell-Ordner geschrieben und mit `--config` übergeben.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TrainingConfig {
+    pub device: TrainingDevice,
+    pub imgsz: u32,
+    pub batch: u32,
+    pub workers: u32,
+    pub optimizer: String,
+    pub momentum: f32,
+    pub lr0: f32,
+    pub lrf: f32,
+    pub weight_decay: f32,
+    pub patience: Option<u32>, // None = 30% der Epochen
+    pub warmup_epochs: f32,
+    pub warmup_momentum: f32,
+    pub warmup_bias_lr: f32,
+    pub amp: bool,
+    pub cos_lr: bool,
+    pub cache: CacheMode,
+
+    // Augmentation
+    pub hsv_h: f32,
+    pub hsv_s: f32,
+    pub hsv_v: f32,
+    pub degrees: f32,
+    pub translate: f32,
+    pub scale: f32,
+    pub shear: f32,
+    pub perspective: f32,
+    pub flipud: f32,
+    pub fliplr: f32,
+    pub mosaic: f32,
+    pub mixup: f32,
+    pub copy_paste: f32,
+}
+
+impl Default for TrainingConfig {
+    // Die Werte, die früher fest in image_data.py standen
+    fn default() -> Self {
+        TrainingConfig {
+            device: TrainingDevice::Cuda,
+            imgsz: 640,
+            batch: 4,
+            workers: 4,
+            optimizer: "SGD".to_string(),
+            momentum: 0.937,
+            lr0: 0.01,
+            lrf: 0.01,
+            weight_decay: 0.0005,
+            patience: None,
+            warmup_epochs: 3.0,
+            warmup_momentum: 0.8,
+            warmup_bias_lr: 0.1,
+            amp: true,
+            cos_lr: true,
+            cache: CacheMode::Ram,
+
+            hsv_h: 0.0,
+            hsv_s: 0.0,
+            hsv_v: 0.0,
+            degrees: 0.0,
+            translate: 0.05,
+            scale: 0.9,
+            shear: 0.0,
+            perspective: 0.0,
+            flipud: 0.0,
+            fliplr: 0.2,
+            mosaic: 0.5,
+            mixup: 0.0,
+            copy_paste: 0.0,
+        }
+    }
+}
+
+// Eigene Presets aus der UI
+pub const TRAINING_PRESETS_FILE: &str = "training_presets.yaml";
+
+impl TrainingConfig {
+    /// Eingebaute Presets. Eigene kommen aus `TRAINING_PRESETS_FILE` dazu.
+    pub fn builtin_presets() -> Vec<(String, TrainingConfig)> {
+        let standard = TrainingConfig::default();
+
+        let cpu = TrainingConfig {
+            device: TrainingDevice::Cpu,
+            imgsz: 416,
+            batch: 2,
+            workers: 2,
+            amp: false,
+            cache: CacheMode::Aus,
+            ..TrainingConfig::default()
+        };
+
+        let big_gpu = TrainingConfig {
+            batch: 16,
+            workers: 8,
+            ..TrainingConfig::default()
+        };
+
+        vec![
+            ("Standard".to_string(), standard),
+            ("CPU (kurz testen)".to_string(), cpu),
+            ("Große GPU".to_string(), big_gpu),
+        ]
+    }
+
+    pub fn load_presets() -> Vec<(String, TrainingConfig)> {
+        let mut presets = TrainingConfig::builtin_presets();
+
+        let saved: HashMap<String, TrainingConfig> = fs::read_to_string(TRAINING_PRESETS_FILE)
+            .ok()
+            .and_then(|s| serde_yaml::from_str(&s).ok())
+            .unwrap_or_default();
+
+        let mut saved: Vec<_> = saved.into_iter().collect();
+        saved.sort_by(|a, b| a.0.cmp(&b.0));
+        presets.extend(saved);
+
+        presets
+    }
+
+    pub fn save_preset(name: &str, config: &TrainingConfig) -> Result<(), FofError> {
+        let mut saved: HashMap<String, TrainingConfig> = fs::read_to_string(TRAINING_PRESETS_FILE)
+            .ok()
+            .and_then(|s| serde_yaml::from_str(&s).ok())
+            .unwrap_or_default();
+
+        saved.insert(name.to_string(), config.clone());
+
+        let yaml =
+            serde_yaml::to_string(&saved).map_err(|e| FofError::YamlParseError(e.to_string()))?;
+        fs::write(TRAINING_PRESETS_FILE, yaml)?;
+        Ok(())
+    }
+
+    /// Hier steht die Config, mit der das Modell zuletzt trainiert wurde.
+    pub fn path(model_name: &str) -> String {
+        format!("runs/detect/{}/training_config.yaml", model_name)
+    }
+
+    pub fn save_for_model(&self, model_name: &str) -> Result<(), FofError> {
+        let yaml =
+            serde_yaml::to_string(self).map_err(|e| FofError::YamlParseError(e.to_string()))?;
+        fs::write(TrainingConfig::path(model_name), yaml)?;
+        Ok(())
+    }
+
+    pub fn load_for_model(model_name: &str) -> Result<TrainingConfig, FofError> {
+        let path = TrainingConfig::path(model_name);
+        let content =
+            fs::read_to_string(&path).map_err(|_| FofError::FailedReadingFile(path.clone()))?;
+        serde_yaml::from_str(&content).map_err(|e| FofError::YamlParseError(e.to_string()))
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct Model {
     pub name: String,
     pub rating: f64,
-    pub dataset_type: DatasetType,
+    pub dataset_type: Option<DatasetType>, // None bei kaputten / fremden Ordnern
+    pub metadata: ModelMetadata,
+    pub status: ModelStatus,
+    pub status_reason: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModelStatus {
+    Untrained, // erstellt, aber noch keine Metriken / Gewichte
+    Training,
+    Trained,
+    Corrupt,  // sieht aus wie ein Modell, aber es fehlt etwas
+    Foreign,  // kein Modell, z. B. ein val Ordner
+    Ensemble, // virtuell, siehe `ensemble::Ensemble`
+}
+
+impl ModelStatus {
+    /// Kann man damit vorhersagen? Trainieren geht nur ohne `Ensemble` (siehe `Model::can_train`).
+    pub fn is_usable(&self) -> bool {
+        matches!(
+            self,
+            ModelStatus::Trained | ModelStatus::Training | ModelStatus::Ensemble
+        )
+    }
+}
+
+impl ToString for ModelStatus {
+    fn to_string(&self) -> String {
+        match self {
+            ModelStatus::Untrained => "Untrainiert".to_string(),
+            ModelStatus::Training => "Trainiert gerade".to_string(),
+            ModelStatus::Trained => "Trainiert".to_string(),
+            ModelStatus::Corrupt => "Kaputt".to_string(),
+            ModelStatus::Foreign => "Kein Modell".to_string(),
+            ModelStatus::Ensemble => "Ensemble".to_string(),
+        }
+    }
+}
+
+/// Welche Daten beim Training im Dataset lagen.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DatasetSnapshot {
+    pub data_yaml: String,
+    pub train_images: usize,
+    pub val_images: usize,
+    pub fingerprint: String, // Hash über Dateinamen + Größen, ändert sich wenn neue Bilder gelabelt werden
+    pub taken_at: String,
+}
+
+#[derive(Deserialize)]
+struct DataYamlSplits {
+    train: String,
+    val: String,
+}
+
+impl DatasetSnapshot {
+    pub fn take(dataset_type: &DatasetType) -> Result<Self, FofError> {
+        use std::hash::{Hash, Hasher};
+
+        let dataset_dir = dataset_type.dir();
+        let data_yaml = dataset_type.data_yaml();
+        let content = fs::read_to_string(&data_yaml)
+            .map_err(|_| FofError::FailedReadingFile(data_yaml.clone()))?;
+        let splits: DataYamlSplits =
+            serde_yaml::from_str(&content).map_err(|e| FofError::YamlParseError(e.to_string()))?;
+
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+
+        let mut count_split = |split: &str| -> usize {
+            let mut files: Vec<(String, u64)> = fs::read_dir(Path::new(&dataset_dir).join(split))
+                .map(|entries| {
+                    entries
+                        .flatten()
+                        .map(|e| {
+                            let size = e.metadata().map(|m| m.len()).unwrap_or(0);
+                            (e.file_name().to_string_lossy().to_string(), size)
+                        })
+                        .collect()
+                })
+                .unwrap_or_default();
+            files.sort();
+            files.hash(&mut hasher);
+            files.len()
+        };
+
+        let train_images = count_split(&splits.train);
+        let val_images = count_split(&splits.val);
+
+        Ok(DatasetSnapshot {
+            data_yaml,
+            train_images,
+            val_images,
+            fingerprint: format!("{:016x}", hasher.finish()),
+            taken_at: Local::now().to_rfc3339(),
+        })
+    }
+}
+
+/// Steht als `model.json` neben den Ergebnissen von ultralytics in `runs/detect/<name>`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ModelMetadata {
+    pub base_model: String,
+    pub dataset_type: String,
+    pub created_at: String,
+    pub total_epochs: u32, // über alle Trainings zusammen
+    pub dataset_snapshot: Option<DatasetSnapshot>,
+    pub notes: String,
+    pub tags: Vec<String>,
+    pub parent: Option<String>,
+    pub interrupted: Option<InterruptedTraining>, // letztes Training abgebrochen, kann fortgesetzt werden
+}
+
+/// Ein abgebrochenes Training, das man mit `--resume` fortsetzen kann.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct InterruptedTraining {
+    pub epochen: usize,     // so viele sollten es werden
+    pub epochs_done: usize, // letzte fertige Epoche in results.csv
+    pub config: TrainingConfig,
+}
+
+#[derive(Deserialize, Default)]
+struct ArgsYamlBase {
+    model: Option<String>,
+    data: Option<String>,
+}
+
+impl ModelMetadata {
+    pub fn path(model_name: &str) -> String {
+        format!("runs/detect/{}/model.json", model_name)
+    }
+
+    /// Liest `model.json`. Ältere Modelle ohne die Datei bekommen Werte aus `args.yaml` und `results.csv`.
+    pub fn load(model_name: &str) -> Result<Self, FofError> {
+        let path = ModelMetadata::path(model_name);
+        if Path::new(&path).exists() {
+            let content =
+                fs::read_to_string(&path).map_err(|_| FofError::FailedReadingFile(path.clone()))?;
+            return serde_json::from_str(&content)
+                .map_err(|e| FofError::JsonParseError(e.to_string()));
+        }
+
+        let args_path = format!("runs/detect/{}/args.yaml", model_name);
+        let args: ArgsYamlBase = fs::read_to_string(&args_path)
+            .ok()
+            .and_then(|s| serde_yaml::from_str(&s).ok())
+            .unwrap_or_default();
+
+        let created_at = fs::metadata(format!("runs/detect/{}", model_name))
+            .and_then(|m| m.created().or(m.modified()))
+            .map(|t| chrono::DateTime::<Local>::from(t).to_rfc3339())
+            .unwrap_or_default();
+
+        let total_epochs = read_metrics_csv(format!("runs/detect/{}/results.csv", model_name))
+            .map(|e| e.len() as u32)
+            .unwrap_or(0);
+
+        Ok(ModelMetadata {
+            base_model: args
+                .model
+                .map(|m| m.trim_end_matches(".pt").to_string())
+                .unwrap_or_default(),
+            dataset_type: args
+                .data
+                .and_then(|d| {
+                    d.strip_prefix("dataset_")
+                        .and_then(|d| d.split('/').next())
+                        .map(|d| d.to_string())
+                })
+                .unwrap_or_default(),
+            created_at,
+            total_epochs,
+            ..Default::default()
+        })
+    }
+
+    pub fn save(&self, model_name: &str) -> Result<(), FofError> {
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| FofError::JsonParseError(e.to_string()))?;
+        fs::write(ModelMetadata::path(model_name), json)?;
+        Ok(())
+    }
+
+    /// Lädt, ändert und speichert `model.json` in einem Schritt.
+    pub fn update(model_name: &str, f: impl FnOnce(&mut ModelMetadata)) -> Result<(), FofError> {
+        let mut metadata = ModelMetadata::load(model_name)?;
+        f(&mut metadata);
+        metadata.save(model_name)
+    }
 }
 
 #[derive(Deserialize)]
@@ -71,71 +717,316 @@ struct ArgsYaml {
 }
 
 impl Model {
-    pub fn new(name: String, rating: f64, dataset_type: DatasetType) -> Self {
+    pub fn new(
+        name: String,
+        rating: f64,
+        dataset_type: DatasetType,
+        metadata: ModelMetadata,
+    ) -> Self {
         Model {
             name,
             rating,
-            dataset_type,
+            dataset_type: Some(dataset_type),
+            metadata,
+            status: ModelStatus::Trained,
+            status_reason: None,
+        }
+    }
+
+    fn unusable(name: &str, status: ModelStatus, reason: impl Into<String>) -> Self {
+        Model {
+            name: name.to_string(),
+            rating: 0.0,
+            dataset_type: get_dataset_type(name).ok(),
+            metadata: ModelMetadata::load(name).unwrap_or_default(),
+            status,
+            status_reason: Some(reason.into()),
         }
     }
+
+    pub fn is_usable(&self) -> bool {
+        self.status.is_usable()
+    }
+
+    /// Untrainierte Modelle kann man trainieren, wenn es schon Gewichte gibt (z. B. nach einem Fork).
+    pub fn can_train(&self) -> bool {
+        if self.status == ModelStatus::Ensemble {
+            return false;
+        }
+
+        self.is_usable()
+            || (self.status == ModelStatus::Untrained
+                && Path::new(&format!("runs/detect/{}/weights/best.pt", self.name)).exists())
+    }
 }
 
-#[derive(Debug, Deserialize)]
-struct Metrics {
+/// Eine Zeile aus der `results.csv` von ultralytics (alle Spalten).
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct EpochMetrics {
+    pub epoch: usize,
+
+    #[serde(rename = "train/box_loss")]
+    pub box_loss: f64,
+
+    #[serde(rename = "train/cls_loss")]
+    pub cls_loss: f64,
+
+    #[serde(rename = "train/dfl_loss")]
+    pub dfl_loss: f64,
+
     #[serde(rename = "metrics/precision(B)")]
-    precision: f64,
+    pub precision: f64,
 
     #[serde(rename = "metrics/recall(B)")]
-    recall: f64,
+    pub recall: f64,
 
     #[serde(rename = "metrics/mAP50(B)")]
-    map_50: f64,
+    pub map_50: f64,
 
     #[serde(rename = "metrics/mAP50-95(B)")]
-    map_50_95: f64,
+    pub map_50_95: f64,
+
+    #[serde(rename = "val/box_loss")]
+    pub val_box_loss: f64,
+
+    #[serde(rename = "val/cls_loss")]
+    pub val_cls_loss: f64,
+
+    #[serde(rename = "val/dfl_loss")]
+    pub val_dfl_loss: f64,
+
+    #[serde(rename = "lr/pg0")]
+    pub lr_pg0: f64,
+
+    #[serde(rename = "lr/pg1")]
+    pub lr_pg1: f64,
+
+    #[serde(rename = "lr/pg2")]
+    pub lr_pg2: f64,
+}
+
+/// Spalten aus `EpochMetrics`, die man plotten kann.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, EnumIter)]
+pub enum MetricColumn {
+    BoxLoss,
+    ClsLoss,
+    DflLoss,
+    Precision,
+    Recall,
+    Map50,
+    Map50_95,
+    ValBoxLoss,
+    ValClsLoss,
+    ValDflLoss,
+    LrPg0,
+    LrPg1,
+    LrPg2,
+}
+
+impl MetricColumn {
+    pub fn value(&self, m: &EpochMetrics) -> f64 {
+        match self {
+            MetricColumn::BoxLoss => m.box_loss,
+            MetricColumn::ClsLoss => m.cls_loss,
+            MetricColumn::DflLoss => m.dfl_loss,
+            MetricColumn::Precision => m.precision,
+            MetricColumn::Recall => m.recall,
+            MetricColumn::Map50 => m.map_50,
+            MetricColumn::Map50_95 => m.map_50_95,
+            MetricColumn::ValBoxLoss => m.val_box_loss,
+            MetricColumn::ValClsLoss => m.val_cls_loss,
+            MetricColumn::ValDflLoss => m.val_dfl_loss,
+            MetricColumn::LrPg0 => m.lr_pg0,
+            MetricColumn::LrPg1 => m.lr_pg1,
+            MetricColumn::LrPg2 => m.lr_pg2,
+        }
+    }
+}
+
+impl ToString for MetricColumn {
+    fn to_string(&self) -> String {
+        match self {
+            MetricColumn::BoxLoss => "train/box_loss".to_string(),
+            MetricColumn::ClsLoss => "train/cls_loss".to_string(),
+            MetricColumn::DflLoss => "train/dfl_loss".to_string(),
+            MetricColumn::Precision => "precision".to_string(),
+            MetricColumn::Recall => "recall".to_string(),
+            MetricColumn::Map50 => "mAP50".to_string(),
+            MetricColumn::Map50_95 => "mAP50-95".to_string(),
+            MetricColumn::ValBoxLoss => "val/box_loss".to_string(),
+            MetricColumn::ValClsLoss => "val/cls_loss".to_string(),
+            MetricColumn::ValDflLoss => "val/dfl_loss".to_string(),
+            MetricColumn::LrPg0 => "lr/pg0".to_string(),
+            MetricColumn::LrPg1 => "lr/pg1".to_string(),
+            MetricColumn::LrPg2 => "lr/pg2".to_string(),
+        }
+    }
+}
+
+pub fn read_metrics_csv<P: AsRef<Path>>(path: P) -> Result<Vec<EpochMetrics>, FofError> {
+    let file = File::open(path.as_ref())
+        .map_err(|_| FofError::FailedReadingFile(path.as_ref().display().to_string()))?;
+
+    // ultralytics füllt die Spaltennamen mit Leerzeichen auf
+    let mut rdr = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_reader(file);
+
+    // Die letzte Zeile kann beim Mitlesen noch halb geschrieben sein, die wird einfach übersprungen
+    Ok(rdr.deserialize().filter_map(|row| row.ok()).collect())
+}
+
+/// Alle Epochen aus `runs/detect/<name>/results.csv`.
+pub fn get_metrics_history(model_name: &str) -> Result<Vec<EpochMetrics>, FofError> {
+    let history = read_metrics_csv(format!("runs/detect/{}/results.csv", model_name))?;
+    if history.is_empty() {
+        return Err(FofError::NoMetricsFoundForModel(model_name.to_string()));
+    }
+    Ok(history)
+}
+
+/// Die Epoche mit dem höchsten Score (gleiche Formel wie das Rating).
+pub fn best_epoch<'a>(
+    history: &'a [EpochMetrics],
+    formula: &RatingFormula,
+) -> Option<&'a EpochMetrics> {
+    history.iter().max_by(|a, b| {
+        formula
+            .score(a)
+            .partial_cmp(&formula.score(b))
+            .unwrap_or(std::cmp::Ordering::Equal)
+    })
 }
 
-fn calculate_score(m: &Metrics) -> f64 {
-    0.4 * m.map_50_95 + 0.3 * m.map_50 + 0.15 * m.precision + 0.15 * m.recall
+/// Die Epoche, die ultralytics als `best.pt` speichert. Das entscheidet dort die Fitness
+/// (`0.1 * mAP50 + 0.9 * mAP50-95`), nicht unsere Rating-Formel.
+pub fn best_pt_epoch(history: &[EpochMetrics]) -> Option<&EpochMetrics> {
+    let fitness = |m: &EpochMetrics| 0.1 * m.map_50 + 0.9 * m.map_50_95;
+    // Bei Gleichstand behält ultralytics die frühere Epoche
+    history
+        .iter()
+        .fold(None, |best: Option<&EpochMetrics>, m| match best {
+            Some(b) if fitness(b) >= fitness(m) => Some(b),
+            _ => Some(m),
+        })
 }
 
-fn read_last_metrics(model_name: &str) -> Option<Metrics> {
-    let path = format!("runs/detect/{}/results.csv", model_name);
-    let file = File::open(&path).ok()?;
-    let mut rdr = Reader::from_reader(file);
-    let mut last: Option<Metrics> = None;
+pub const RATING_FORMULA_FILE: &str = "rating_formula.yaml";
+
+/// Wie aus den Metriken das Rating wird, nach dem die Modelle sortiert werden.
+/// Wird in den Einstellungen bearbeitet und in `RATING_FORMULA_FILE` gespeichert.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RatingFormula {
+    pub weight_map_50_95: f64,
+    pub weight_map_50: f64,
+    pub weight_precision: f64,
+    pub weight_recall: f64,
+
+    /// Rating aus den Klassen-Metriken vom letzten TestVals Lauf statt aus `results.csv`.
+    /// Modelle ohne TestVals fallen auf `results.csv` zurück.
+    pub per_class: bool,
 
-    for result in rdr.deserialize() {
-        if let Ok(row) = result {
-            last = Some(row);
+    /// Wie wichtig eine Klasse ist. Klassen, die hier fehlen, zählen mit 1.0, 0.0 ignoriert sie.
+    pub class_weights: HashMap<String, f64>,
+}
+
+impl Default for RatingFormula {
+    fn default() -> Self {
+        RatingFormula {
+            weight_map_50_95: 0.4,
+            weight_map_50: 0.3,
+            weight_precision: 0.15,
+            weight_recall: 0.15,
+            per_class: false,
+            class_weights: HashMap::new(),
         }
     }
-    last
 }
 
-fn get_rating(model_name: &str) -> Result<f64, FofError> {
+impl RatingFormula {
+    pub fn load() -> RatingFormula {
+        fs::read_to_string(RATING_FORMULA_FILE)
+            .ok()
+            .and_then(|s| serde_yaml::from_str(&s).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) -> Result<(), FofError> {
+        let yaml =
+            serde_yaml::to_string(self).map_err(|e| FofError::YamlParseError(e.to_string()))?;
+        fs::write(RATING_FORMULA_FILE, yaml)?;
+        Ok(())
+    }
+
+    pub fn class_weight(&self, class_name: &str) -> f64 {
+        self.class_weights.get(class_name).copied().unwrap_or(1.0)
+    }
+
+    fn weighted(&self, map_50_95: f64, map_50: f64, precision: f64, recall: f64) -> f64 {
+        self.weight_map_50_95 * map_50_95
+            + self.weight_map_50 * map_50
+            + self.weight_precision * precision
+            + self.weight_recall * recall
+    }
+
+    pub fn score(&self, m: &EpochMetrics) -> f64 {
+        self.weighted(m.map_50_95, m.map_50, m.precision, m.recall)
+    }
+
+    /// Gewichteter Durchschnitt über die Klassen. `None`, wenn keine Klasse Gewicht hat.
+    pub fn score_classes(&self, classes: &[ClassMetrics]) -> Option<f64> {
+        let mut total = 0.0;
+        let mut total_weight = 0.0;
+
+        for c in classes {
+            let weight = self.class_weight(&c.class_name);
+            total += weight * self.weighted(c.map_50_95, c.map_50, c.precision, c.recall);
+            total_weight += weight;
+        }
+
+        if total_weight <= 0.0 {
+            return None;
+        }
+        Some(total / total_weight)
+    }
+}
+
+fn read_last_metrics(model_name: &str) -> Option<EpochMetrics> {
+    get_metrics_history(model_name).ok()?.pop()
+}
+
+fn get_rating(model_name: &str, formula: &RatingFormula) -> Result<f64, FofError> {
+    if formula.per_class {
+        let score = read_class_metrics(model_name)
+            .ok()
+            .and_then(|classes| formula.score_classes(&classes));
+        if let Some(score) = score {
+            return Ok(score);
+        }
+    }
+
     let metrics = read_last_metrics(model_name);
 
     if let Some(m) = metrics {
-        return Ok(calculate_score(&m));
+        return Ok(formula.score(&m));
     }
 
     return Err(FofError::NoMetricsFoundForModel(model_name.to_string()));
 }
 
-fn start_python(args: Vec<&str>) -> Result<String, FofError> {
-    match Command::new("python").args(args).output() {
-        Ok(output) if output.status.success() => {
-            Ok(String::from_utf8_lossy(&output.stdout).to_string())
+pub fn get_dataset_type(name: &str) -> Result<DatasetType, FofError> {
+    if let Some(ensemble) = ensemble::Ensemble::find(name) {
+        return DatasetType::by_name(&ensemble.dataset_type);
+    }
+
+    if let Ok(metadata) = ModelMetadata::load(name) {
+        if let Ok(dataset_type) = DatasetType::by_name(&metadata.dataset_type) {
+            return Ok(dataset_type);
         }
-        Ok(output) => Err(FofError::PythonError(
-            String::from_utf8_lossy(&output.stderr).to_string(),
-        )),
-        Err(e) => Err(FofError::FailedToStartPython),
     }
-}
 
-pub fn get_dataset_type(name: &str) -> Result<DatasetType, FofError> {
     let path = format!("runs/detect/{}/args.yaml", name);
     let file = File::open(&path).map_err(|_| FofError::FailedReadingFile(path.clone()))?;
 
@@ -146,62 +1037,174 @@ pub fn get_dataset_type(name: &str) -> Result<DatasetType, FofError> {
         .data
         .ok_or_else(|| FofError::MissingField(format!("'data' field missing in {}", path)))?;
 
-    if data_field.starts_with("dataset_buildings/") {
-        Ok(DatasetType::Buildings)
-    } else if data_field.starts_with("dataset_level/") {
-        Ok(DatasetType::Level)
-    } else {
-        Err(FofError::UnsupportedDataset(data_field))
+    DatasetType::all()
+        .into_iter()
+        .find(|d| data_field.starts_with(&format!("{}/", d.dir())))
+        .ok_or(FofError::UnsupportedDataset(data_field))
+}
+
+/// Eine Zeile aus `class_metrics.csv`, die `--testvals` und die Validierung nach dem Training pro Klasse schreiben.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct ClassMetrics {
+    #[serde(rename = "class")]
+    pub class_name: String,
+    pub images: usize,
+    pub instances: usize,
+    pub precision: f64,
+    pub recall: f64,
+
+    #[serde(rename = "mAP50")]
+    pub map_50: f64,
+
+    #[serde(rename = "mAP50-95")]
+    pub map_50_95: f64,
+}
+
+pub fn class_metrics_path(model_name: &str) -> String {
+    format!("testvals/val_run_{}/class_metrics.csv", model_name)
+}
+
+/// Liest die Klassen-Metriken vom letzten TestVals Lauf.
+pub fn read_class_metrics(model_name: &str) -> Result<Vec<ClassMetrics>, FofError> {
+    let path = class_metrics_path(model_name);
+    let file = File::open(&path).map_err(|_| FofError::FailedReadingFile(path.clone()))?;
+
+    let mut rdr = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_reader(file);
+
+    rdr.deserialize()
+        .collect::<Result<Vec<ClassMetrics>, _>>()
+        .map_err(|e| FofError::FailedReadingFile(format!("{}: {}", path, e)))
+}
+
+/// Klassennamen aus der `data.yaml` vom Datensatz, nach Klassen-Id sortiert.
+pub fn read_class_names(dataset_type: &DatasetType) -> Result<Vec<(u32, String)>, FofError> {
+    #[derive(Deserialize)]
+    struct DataYamlNames {
+        names: HashMap<u32, String>,
     }
+
+    let path = dataset_type.data_yaml();
+    let yaml = fs::read_to_string(&path).map_err(|_| FofError::FailedReadingFile(path.clone()))?;
+    let data: DataYamlNames =
+        serde_yaml::from_str(&yaml).map_err(|e| FofError::YamlParseError(e.to_string()))?;
+
+    let mut names: Vec<(u32, String)> = data.names.into_iter().collect();
+    names.sort();
+    Ok(names)
 }
 
-pub fn get_testvals(model_name: String) -> Result<(), FofError> {
-    let dataset_type = get_dataset_type(model_name.as_str())?.to_string();
-    let args = vec![
-        "src/image_data.py",
-        "--testvals",
-        "--model-name",
-        model_name.as_str(),
-        "--dataset_type",
-        dataset_type.as_str(),
-    ];
-    let python_output = start_python(args)?;
+/// Startet die Validierung und gibt die Metriken pro Klasse zurück.
+pub fn get_testvals(model_name: String) -> Result<Vec<ClassMetrics>, FofError> {
+    // ultralytics kann nur echte Gewichte validieren
+    if ensemble::Ensemble::find(&model_name).is_some() {
+        return Err(FofError::Failed(format!(
+            "{} ist ein Ensemble, TestVals gibt es nur für die Mitglieder",
+            model_name
+        )));
+    }
 
-    Ok(())
+    let dataset_type = get_dataset_type(model_name.as_str())?;
+    inference_backend::backend().validate(model_name.as_str(), &dataset_type)?;
+    read_class_metrics(model_name.as_str())
 }
 
 pub fn get_all_models() -> Result<Vec<Model>, FofError> {
     let mut models = Vec::new();
     let path = Path::new("runs/detect");
 
+    if !path.exists() {
+        return Ok(models);
+    }
+
     let entries = fs::read_dir(path)
         .map_err(|_| FofError::FailedReadingDirectory(path.display().to_string()))?;
 
-    for entry_result in entries {
-        let entry = entry_result
-            .map_err(|_| FofError::Failed("Failed reading entry results".to_string()))?;
+    let training = training_queue::running_models();
+    let formula = RatingFormula::load();
 
-        let metadata = entry
-            .metadata()
-            .map_err(|_| FofError::Failed("Failed reading metadata".to_string()))?;
+    // Ein kaputter Ordner soll nicht die ganze Liste leer machen, deshalb bekommt jeder Ordner einen Status
+    for entry in entries.flatten() {
+        if !entry.metadata().map(|m| m.is_dir()).unwrap_or(false) {
+            continue;
+        }
 
-        if metadata.is_dir() {
-            let filename = entry.file_name();
+        let Some(name) = entry.file_name().to_str().map(|n| n.to_string()) else {
+            continue;
+        };
 
-            let filename = filename.to_str().ok_or(FofError::FailedReadingFile(
-                "Failed reading file name".to_string(),
-            ))?;
+        models.push(inspect_model(&name, training.contains(&name), &formula));
+    }
 
-            let rating = get_rating(filename)?;
-            let dataset_type = get_dataset_type(filename)?;
-            let model = Model::new(filename.to_string(), rating, dataset_type);
-            models.push(model);
-        }
+    for ensemble in ensemble::Ensemble::load_all() {
+        let model = ensemble.as_model(&models);
+        models.push(model);
     }
 
     Ok(models)
 }
 
+fn inspect_model(name: &str, is_training: bool, formula: &RatingFormula) -> Model {
+    let dir = format!("runs/detect/{}", name);
+    let has = |file: &str| Path::new(&dir).join(file).exists();
+
+    if !has("args.yaml") && !has("model.json") {
+        return Model::unusable(
+            name,
+            ModelStatus::Foreign,
+            "Keine args.yaml, kein Trainings-Ordner",
+        );
+    }
+
+    let dataset_type = match get_dataset_type(name) {
+        Ok(dataset_type) => dataset_type,
+        Err(e) => {
+            return Model::unusable(
+                name,
+                ModelStatus::Corrupt,
+                format!("Dataset unbekannt: {:?}", e),
+            )
+        }
+    };
+
+    let metadata = match ModelMetadata::load(name) {
+        Ok(metadata) => metadata,
+        Err(e) => {
+            return Model::unusable(
+                name,
+                ModelStatus::Corrupt,
+                format!("model.json kaputt: {:?}", e),
+            )
+        }
+    };
+
+    let has_weights = has("weights/best.pt");
+    let rating = get_rating(name, formula);
+
+    let (status, reason) = match (is_training, has_weights, &rating) {
+        (true, _, _) => (ModelStatus::Training, None),
+        (false, true, Ok(_)) => (ModelStatus::Trained, None),
+        (false, false, Ok(_)) => (
+            ModelStatus::Corrupt,
+            Some("results.csv vorhanden, aber weights/best.pt fehlt".to_string()),
+        ),
+        (false, _, Err(_)) => (
+            ModelStatus::Untrained,
+            Some("Noch keine Metriken in results.csv".to_string()),
+        ),
+    };
+
+    Model {
+        name: name.to_string(),
+        rating: rating.unwrap_or(0.0),
+        dataset_type: Some(dataset_type),
+        metadata,
+        status,
+        status_reason: reason,
+    }
+}
+
 pub fn get_avg_confidence(buildings: &[Building]) -> f32 {
     if buildings.is_empty() {
         return 0.0;
@@ -211,10 +1214,34 @@ pub fn get_avg_confidence(buildings: &[Building]) -> f32 {
     sum / buildings.len() as f32
 }
 
+/// Einstellungen für das erste, kurze Training beim Erstellen eines Modells.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CreateModelOptions {
+    pub epochs: u32,
+    pub imgsz: u32,
+}
+
+impl Default for CreateModelOptions {
+    fn default() -> Self {
+        CreateModelOptions {
+            epochs: 1,
+            imgsz: 640,
+        }
+    }
+}
+
+/// Nach einem Training die Epochen zu `total_epochs` im `model.json` dazuzählen.
+pub fn record_trained_epochs(model_name: &str, epochs: u32) -> Result<(), FofError> {
+    ModelMetadata::update(model_name, |m| m.total_epochs += epochs)
+}
+
+/// Blockiert, bis Python fertig ist. Wenn etwas schiefgeht, wird der halb
+/// angelegte Ordner in `runs/detect` wieder gelöscht.
 pub fn create_model(
     model_name: &str,
     dataset_type: DatasetType,
     yolo_model: YoloModel,
+    options: &CreateModelOptions,
 ) -> Result<(), FofError> {
     let model_path = format!("runs/detect/{}", model_name);
 
@@ -222,130 +1249,467 @@ pub fn create_model(
         return Err(FofError::ModelAlreadyExists);
     }
 
-    let dataset_type = dataset_type.to_string();
-    let yolo_model_string = yolo_model.to_string();
+    // Erst schauen, ob sich die Gewichte laden lassen, bevor ein halber Run angelegt wird
+    inference_backend::backend().check_base_weights(&yolo_model)?;
+
+    let res = inference_backend::backend()
+        .create_model(model_name, &dataset_type, &yolo_model, options)
+        .and_then(|_| {
+            let weights = format!("{}/weights/best.pt", model_path);
+            if check_if_exists(&weights)? {
+                Ok(())
+            } else {
+                Err(FofError::FailedReadingFile(weights))
+            }
+        });
+
+    let res = res.and_then(|_| {
+        ModelMetadata {
+            base_model: yolo_model.to_string(),
+            dataset_type: dataset_type.to_string(),
+            created_at: Local::now().to_rfc3339(),
+            total_epochs: options.epochs,
+            dataset_snapshot: DatasetSnapshot::take(&dataset_type).ok(),
+            ..Default::default()
+        }
+        .save(model_name)
+    });
+
+    if res.is_err() && check_if_exists(&model_path)? {
+        fs::remove_dir_all(&model_path)
+            .map_err(|_| FofError::FailedDeletingDirectory(model_path.clone()))?;
+    }
 
-    let args = vec![
-        "src/image_data.py",
-        "--create-model",
-        "--base",
-        yolo_model_string.as_str(),
-        "--model-name",
-        model_name,
-        "--dataset_type",
-        dataset_type.as_str(),
-    ];
+    res
+}
 
-    let python_output = start_python(args);
+/// Neues Modell, das mit `weights/best.pt` von `parent` startet und danach unabhängig trainiert wird.
+/// Die Trainings-Config vom Parent wird mitkopiert, `results.csv` nicht.
+pub fn fork_model(parent: &str, model_name: &str) -> Result<(), FofError> {
+    let parent_path = format!("runs/detect/{}", parent);
+    let model_path = format!("runs/detect/{}", model_name);
 
-    return Ok(());
+    if !check_if_exists(&format!("{}/weights/best.pt", parent_path))? {
+        return Err(FofError::ModelNotFound(parent.to_string()));
+    }
+
+    if check_if_exists(&model_path)? {
+        return Err(FofError::ModelAlreadyExists);
+    }
+
+    let parent_metadata = ModelMetadata::load(parent)?;
+
+    let res = fs::create_dir_all(format!("{}/weights", model_path))
+        .map_err(FofError::from)
+        .and_then(|_| {
+            fs::copy(
+                format!("{}/weights/best.pt", parent_path),
+                format!("{}/weights/best.pt", model_path),
+            )
+            .map_err(|e| FofError::FailedToCopyData(format!("best.pt von {}: {}", parent, e)))
+        })
+        .and_then(|_| {
+            let config = TrainingConfig::path(parent);
+            if Path::new(&config).exists() {
+                fs::copy(&config, TrainingConfig::path(model_name))?;
+            }
+            // Gleiche Gewichte, also passen auch die Schwellen pro Klasse erstmal
+            let thresholds = class_thresholds::ClassThresholds::path(parent);
+            if Path::new(&thresholds).exists() {
+                fs::copy(
+                    &thresholds,
+                    class_thresholds::ClassThresholds::path(model_name),
+                )?;
+            }
+            Ok(())
+        })
+        .and_then(|_| {
+            ModelMetadata {
+                base_model: parent_metadata.base_model.clone(),
+                dataset_type: parent_metadata.dataset_type.clone(),
+                created_at: Local::now().to_rfc3339(),
+                total_epochs: 0,
+                dataset_snapshot: None,
+                notes: String::new(),
+                tags: parent_metadata.tags.clone(),
+                parent: Some(parent.to_string()),
+                interrupted: None,
+            }
+            .save(model_name)
+        });
+
+    if res.is_err() && check_if_exists(&model_path)? {
+        fs::remove_dir_all(&model_path)
+            .map_err(|_| FofError::FailedDeletingDirectory(model_path.clone()))?;
+    }
+
+    res
 }
 
 pub fn delete_model(model_name: &str) -> Result<(), FofError> {
+    if ensemble::Ensemble::find(model_name).is_some() {
+        return ensemble::Ensemble::delete(model_name);
+    }
+
     let model_path = format!("runs/detect/{}", model_name);
 
     if !check_if_exists(&model_path)? {
         return Err(FofError::ModelNotFound(model_name.to_string()));
     }
 
+    inference_server::shutdown(model_name);
+    prediction_cache::clear_model(model_name)?;
+
     fs::remove_dir_all(&model_path).map_err(|_| FofError::FailedDeletingDirectory(model_path))?;
     Ok(())
 }
 
-pub fn start_training(model_name: &str, epochen: i32) -> Result<Child, FofError> {
-    let dataset_type = get_dataset_type(model_name)?.to_string();
+/// `Ok(None)` heißt, das Backend hat schon fertig trainiert (z. B. das Fake Backend).
+/// Mit `resume` wird ein abgebrochenes Training von `last.pt` / `resume.pt` fortgesetzt,
+/// dann bleiben Config und Dataset-Snapshot vom ersten Start.
+pub fn start_training(
+    model_name: &str,
+    epochen: i32,
+    config: &TrainingConfig,
+    resume: bool,
+) -> Result<Option<Child>, FofError> {
+    let dataset_type = get_dataset_type(model_name)?;
 
     let model_path = format!("runs/detect/{}", model_name);
     fs::metadata(&model_path).map_err(|_| FofError::ModelNotFound(model_name.to_string()))?;
 
-    let child = Command::new("python")
-        .arg("src/image_data.py")
-        .arg("--train")
-        .arg("--model-name")
-        .arg(model_name)
-        .arg("--epochs")
-        .arg(epochen.to_string())
-        .arg("--dataset_type")
-        .arg(dataset_type)
-        .spawn()
-        .map_err(|e| {
-            eprintln!("Fehler beim Starten des Trainingsprozesses: {}", e);
-            FofError::FailedToStartPython
-        })?;
-
-    Ok(child)
-}
-
-pub fn stop_training(child: &mut Child) -> Result<(), FofError> {
-    child.kill().map_err(|_| FofError::FailedToStopTraining)?;
-    child.wait().map_err(|_| FofError::FailedToStopTraining)?;
-    Ok(())
+    if !resume {
+        config.save_for_model(model_name)?;
+
+        let snapshot = DatasetSnapshot::take(&dataset_type).ok();
+        ModelMetadata::update(model_name, |m| m.dataset_snapshot = snapshot)?;
+    }
+
+    // Übrig vom letzten Stop, sonst hört das neue Training nach der ersten Epoche auf
+    let _ = fs::remove_file(stop_request_path(model_name));
+
+    inference_backend::backend().train(model_name, &dataset_type, epochen, config, resume)
 }
 
-fn check_if_exists<P: Debug + AsRef<Path> + Display>(path: &P) -> Result<bool, FofError> {
-    let exists = fs::exists(&path).map_err(|_| FofError::FailedReadingFile(path.to_string()))?;
-    Ok(exists)
+/// Python schaut nach jeder Epoche, ob es diese Datei gibt, und hört dann sauber auf.
+pub fn stop_request_path(model_name: &str) -> String {
+    format!("runs/detect/{}/stop_requested", model_name)
 }
 
-fn create_communication(model_name: &str) -> Result<(), FofError> {
-    let path = format!("Communication/{}", model_name);
-    fs::create_dir(path).map_err(|e| FofError::FailedCreatingCommunication(e.to_string()))?;
+/// Bittet das Training, nach der aktuellen Epoche aufzuhören (inkl. finaler Validierung).
+pub fn request_stop(model_name: &str) -> Result<(), FofError> {
+    fs::write(stop_request_path(model_name), Local::now().to_rfc3339())?;
     Ok(())
 }
 
-fn remove_communication(model_name: &str) -> Result<(), FofError> {
-    let comm_path = format!("Communication/{}", model_name);
+/// Welche Gewichte es für ein Modell gibt, mit Änderungszeit.
+#[derive(Debug, Clone, Default)]
+pub struct WeightsStatus {
+    pub best: Option<std::time::SystemTime>,
+    pub last: Option<std::time::SystemTime>,
+    pub resume: Option<std::time::SystemTime>, // Kopie von last.pt mit Optimizer nach einem Stop
+}
 
-    if !check_if_exists(&comm_path)? {
-        return Err(FofError::FailedReadingDirectory(comm_path.to_string()));
+impl WeightsStatus {
+    pub fn of(model_name: &str) -> WeightsStatus {
+        let modified = |file: &str| {
+            fs::metadata(format!("runs/detect/{}/weights/{}", model_name, file))
+                .and_then(|m| m.modified())
+                .ok()
+        };
+
+        WeightsStatus {
+            best: modified("best.pt"),
+            last: modified("last.pt"),
+            resume: modified("resume.pt"),
+        }
     }
 
-    fs::remove_dir_all(&comm_path)
-        .map_err(|_| FofError::FailedDeletingDirectory(comm_path.to_string()))?;
-    Ok(())
+    pub fn can_resume(&self) -> bool {
+        self.resume.is_some() || self.last.is_some()
+    }
+}
+
+pub fn check_if_exists<P: Debug + AsRef<Path> + Display>(path: &P) -> Result<bool, FofError> {
+    let exists = fs::exists(&path).map_err(|_| FofError::FailedReadingFile(path.to_string()))?;
+    Ok(exists)
+}
+
+/// Modellordner oder Ensemble mit dem Namen.
+fn check_model_exists(model_name: &str) -> Result<(), FofError> {
+    let model_path = format!("runs/detect/{}", model_name);
+    if check_if_exists(&model_path)? || ensemble::Ensemble::find(model_name).is_some() {
+        return Ok(());
+    }
+    Err(FofError::ModelNotFound(model_path))
 }
 
 pub fn get_prediction<P>(model_name: &str, screenshot_path: &P) -> Result<Vec<Building>, FofError>
 where
     P: AsRef<Path> + Debug + Display,
 {
-    let model_path = format!("runs/detect/{}", model_name);
-    if !check_if_exists(&model_path)? {
-        return Err(FofError::ModelNotFound(model_path.to_string()));
-    }
+    predict(model_name, screenshot_path, true)
+}
+
+/// Wie `get_prediction`, aber ohne den Cache. Für temporäre Bilder (Kacheln),
+/// die nach der Vorhersage wieder gelöscht werden und den Cache nur füllen würden.
+pub fn get_prediction_uncached<P>(
+    model_name: &str,
+    screenshot_path: &P,
+) -> Result<Vec<Building>, FofError>
+where
+    P: AsRef<Path> + Debug + Display,
+{
+    predict(model_name, screenshot_path, false)
+}
+
+pub(crate) fn predict<P>(
+    model_name: &str,
+    screenshot_path: &P,
+    use_cache: bool,
+) -> Result<Vec<Building>, FofError>
+where
+    P: AsRef<Path> + Debug + Display,
+{
+    check_model_exists(model_name)?;
 
     if !check_if_exists(&screenshot_path)? {
         return Err(FofError::FailedReadingFile(screenshot_path.to_string()));
     }
 
-    remove_communication(model_name);
-    create_communication(model_name);
+    // Die Mitglieder gehen selbst durch den Cache
+    if let Some(ensemble) = ensemble::Ensemble::find(model_name) {
+        return ensemble.predict(screenshot_path, use_cache);
+    }
 
-    let target_screenshot_path = format!("Communication/{}/screenshot.png", model_name);
+    if use_cache {
+        if let Some(buildings) = prediction_cache::get(model_name, screenshot_path) {
+            return Ok(buildings);
+        }
+    }
 
-    fs::copy(screenshot_path, target_screenshot_path).map_err(|e| {
-        FofError::FailedToCopyData(String::from(
-            "Failed to copy screenshot to Communication directory.",
-        ))
-    })?;
+    let buildings =
+        inference_backend::backend().predict(model_name, &screenshot_path.to_string())?;
 
-    dbg!("Starte jetzt python");
-    let args = vec!["src/image_data.py", "--predict", "--model-name", model_name];
-    let python_output = start_python(args)?;
+    if use_cache {
+        if let Err(e) = prediction_cache::store(model_name, screenshot_path, &buildings) {
+            eprintln!("Konnte Vorhersage nicht cachen: {:?}", e);
+        }
+    }
 
-    let data_path = format!("Communication/{}/data.json", model_name);
-    if !check_if_exists(&data_path)? {
-        return Err(FofError::FailedReadingFile(
-            format!("data.json in {} nicht gefunden", data_path).to_string(),
-        ));
+    Ok(buildings)
+}
+
+/// Vorhersage in überlappenden Kacheln für kleine Objekte (Mauern, Level-Ziffern).
+/// Jede Kachel geht einzeln an das Modell (am Cache vorbei, die Kacheln sind nur temporär),
+/// die Boxen werden zurück in Pixel vom ganzen Bild gerechnet und an den Kachelrändern zusammengefasst.
+pub fn get_tiled_prediction<P>(
+    model_name: &str,
+    screenshot_path: &P,
+    settings: &split_image::TileSettings,
+) -> Result<Vec<Building>, FofError>
+where
+    P: AsRef<Path> + Debug + Display,
+{
+    check_model_exists(model_name)?;
+
+    let image = image::open(screenshot_path.as_ref())
+        .map_err(|e| FofError::FailedReadingFile(format!("{}: {}", screenshot_path, e)))?
+        .to_rgba8();
+
+    let workspace = communication::Workspace::create(model_name)?;
+    let tiles = split_image::tiles(&image, settings);
+    let mut buildings = vec![];
+
+    for (i, tile) in tiles.iter().enumerate() {
+        let tile_path = workspace.file(&format!("tile_{}.png", i));
+        tile.image
+            .save(&tile_path)
+            .map_err(|e| FofError::Failed(format!("Kachel {} speichern: {}", tile_path, e)))?;
+
+        // Python liefert alles ab conf=0.0, das Rauschen soll nicht mit echten Boxen verschmelzen
+        for mut building in get_prediction_uncached(model_name, &tile_path)? {
+            if building.confidence < settings.min_confidence {
+                continue;
+            }
+            let (x1, y1, x2, y2) = building.bounding_box;
+            let (dx, dy) = (tile.x as f32, tile.y as f32);
+            building.bounding_box = (x1 + dx, y1 + dy, x2 + dx, y2 + dy);
+            buildings.push((building, i));
+        }
     }
 
-    let file = File::open(data_path).map_err(|e| FofError::FailedReadingFile(e.to_string()))?;
+    let rects: Vec<(f32, f32, f32, f32)> = tiles.iter().map(|t| t.rect()).collect();
+    Ok(filter_buildings::merge_tile_duplicates(
+        buildings,
+        &rects,
+        settings.merge_iou,
+    ))
+}
 
-    let reader = BufReader::new(file);
+#[derive(Debug, Clone)]
+pub struct BatchProgress {
+    pub done: usize,
+    pub total: usize,
+    pub image_path: String,
+    pub result: Result<Vec<Building>, FofError>,
+}
 
-    let buildings: Vec<Building> =
-        serde_json::from_reader(reader).map_err(|e| FofError::FailedReadingFile(e.to_string()))?;
+/// Vorhersagen für viele Bilder mit dem gleichen (einmal geladenen) Modell.
+/// `on_result` wird nach jedem Bild aufgerufen, über `cancel` kann man zwischen zwei Bildern abbrechen.
+/// Gibt zurück, wie viele Bilder bearbeitet wurden.
+pub fn get_predictions<P>(
+    model_name: &str,
+    image_paths: &[P],
+    cancel: &AtomicBool,
+    mut on_result: impl FnMut(BatchProgress),
+) -> Result<usize, FofError>
+where
+    P: AsRef<Path> + Debug + Display,
+{
+    check_model_exists(model_name)?;
 
-    Ok(buildings)
+    let total = image_paths.len();
+    let mut done = 0;
+
+    for image_path in image_paths {
+        if cancel.load(Ordering::SeqCst) {
+            break;
+        }
+
+        let result = get_prediction(model_name, image_path);
+        done += 1;
+
+        on_result(BatchProgress {
+            done,
+            total,
+            image_path: image_path.to_string(),
+            result,
+        });
+    }
+
+    Ok(done)
+}
+
+/// Schreibt die Vorhersage für `image_path` als `<output_dir>/<bildname>.json`.
+pub fn write_prediction_json<P: AsRef<Path>>(
+    output_dir: P,
+    image_path: &str,
+    buildings: &[Building],
+) -> Result<PathBuf, FofError> {
+    let stem = Path::new(image_path)
+        .file_stem()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_string();
+    let json_path = output_dir.as_ref().join(format!("{}.json", stem));
+
+    let json = serde_json::to_string_pretty(buildings)
+        .map_err(|e| FofError::JsonParseError(e.to_string()))?;
+    fs::write(&json_path, json)?;
+
+    Ok(json_path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_file(name: &str, content: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("fof_{}_{}", uuid::Uuid::new_v4(), name));
+        fs::write(&path, content).unwrap();
+        path
+    }
+
+    fn epoch(epoch: usize, map_50: f64, map_50_95: f64) -> EpochMetrics {
+        EpochMetrics {
+            epoch,
+            map_50,
+            map_50_95,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn metrics_csv_with_padded_header_and_half_written_row() {
+        let path = temp_file(
+            "results.csv",
+            "                  epoch,         train/box_loss,    metrics/precision(B),       metrics/mAP50(B),    metrics/mAP50-95(B)\n\
+             1,1.5,0.4,0.5,0.3\n\
+             2,1.2,0.6,0.7,0.45\n\
+             3,1.1,0.",
+        );
+        let history = read_metrics_csv(&path).unwrap();
+        let _ = fs::remove_file(&path);
+
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[1].epoch, 2);
+        assert_eq!(history[1].box_loss, 1.2);
+        assert_eq!(history[1].map_50_95, 0.45);
+        // Fehlende Spalten bleiben 0
+        assert_eq!(history[1].recall, 0.0);
+    }
+
+    #[test]
+    fn rating_formula_fills_missing_fields_with_defaults() {
+        let formula: RatingFormula =
+            serde_yaml::from_str("weight_map_50_95: 1.0\nclass_weights:\n  wall: 0.0\n").unwrap();
+        let default = RatingFormula::default();
+
+        assert_eq!(formula.weight_map_50_95, 1.0);
+        assert_eq!(formula.weight_map_50, default.weight_map_50);
+        assert!(!formula.per_class);
+        assert_eq!(formula.class_weight("wall"), 0.0);
+        assert_eq!(formula.class_weight("cannon"), 1.0);
+    }
+
+    #[test]
+    fn rating_formula_scores_epochs_and_classes() {
+        let formula = RatingFormula {
+            weight_map_50_95: 0.5,
+            weight_map_50: 0.5,
+            weight_precision: 0.0,
+            weight_recall: 0.0,
+            per_class: true,
+            class_weights: HashMap::from([("wall".to_string(), 0.0), ("cannon".to_string(), 3.0)]),
+        };
+        assert!((formula.score(&epoch(1, 0.8, 0.4)) - 0.6).abs() < 1e-9);
+
+        let class = |name: &str, map: f64| ClassMetrics {
+            class_name: name.to_string(),
+            map_50: map,
+            map_50_95: map,
+            ..Default::default()
+        };
+        // wall zählt nicht, cannon dreifach
+        let classes = vec![
+            class("wall", 0.0),
+            class("cannon", 0.8),
+            class("mortar", 0.4),
+        ];
+        let score = formula.score_classes(&classes).unwrap();
+        assert!((score - (3.0 * 0.8 + 0.4) / 4.0).abs() < 1e-9);
+
+        assert_eq!(formula.score_classes(&[class("wall", 1.0)]), None);
+    }
+
+    #[test]
+    fn best_pt_epoch_uses_ultralytics_fitness() {
+        let history = vec![
+            epoch(1, 0.9, 0.3), // bestes mAP50, aber schlechtere Fitness
+            epoch(2, 0.6, 0.5),
+            epoch(3, 0.6, 0.5), // Gleichstand, ultralytics behält Epoche 2
+        ];
+        assert_eq!(best_pt_epoch(&history).unwrap().epoch, 2);
+
+        let formula = RatingFormula {
+            weight_map_50_95: 0.0,
+            weight_map_50: 1.0,
+            weight_precision: 0.0,
+            weight_recall: 0.0,
+            ..Default::default()
+        };
+        assert_eq!(best_epoch(&history, &formula).unwrap().epoch, 1);
+        assert!(best_pt_epoch(&[]).is_none());
+    }
 }
@@ -0,0 +1,465 @@
+use crate::image_data_wrapper::{
+    check_if_exists, class_metrics_path, read_class_names, Building, CreateModelOptions,
+    DatasetType, TrainingConfig, YoloModel,
+};
+use crate::prelude::*;
+use crate::run_log::RunKind;
+
+use std::process::Stdio;
+use std::sync::OnceLock;
+
+/// Alles, was wir von einem YOLO-Backend brauchen. Die UI und `image_data_wrapper`
+/// reden nur noch über diesen Trait mit Python (oder eben nicht).
+pub trait InferenceBackend: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    fn predict(&self, model_name: &str, screenshot_path: &str) -> Result<Vec<Building>, FofError>;
+
+    /// Wird vor `create_model` aufgerufen. Schlägt fehl, wenn sich die Basis-Gewichte nicht laden lassen.
+    fn check_base_weights(&self, yolo_model: &YoloModel) -> Result<(), FofError>;
+
+    fn create_model(
+        &self,
+        model_name: &str,
+        dataset_type: &DatasetType,
+        yolo_model: &YoloModel,
+        options: &CreateModelOptions,
+    ) -> Result<(), FofError>;
+
+    /// Gibt `None` zurück, wenn das Training schon fertig ist, wenn die Funktion zurückkehrt.
+    /// Die Config liegt zu dem Zeitpunkt schon unter `TrainingConfig::path(model_name)`.
+    /// `resume` setzt ein abgebrochenes Training fort, statt ein neues anzufangen.
+    fn train(
+        &self,
+        model_name: &str,
+        dataset_type: &DatasetType,
+        epochen: i32,
+        config: &TrainingConfig,
+        resume: bool,
+    ) -> Result<Option<Child>, FofError>;
+
+    fn validate(&self, model_name: &str, dataset_type: &DatasetType) -> Result<(), FofError>;
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum BackendKind {
+    Python,
+    Native,        // Vorhersagen mit best.onnx in Rust, Training weiter über Python
+    Fake(PathBuf), // Ordner mit aufgenommenen data.json Dateien
+}
+
+impl BackendKind {
+    /// `FOF_BACKEND=fake` (optional mit `FOF_FAKE_DATA=<ordner>`) oder `--fake-backend`,
+    /// `FOF_BACKEND=native` oder `--native-backend`
+    pub fn from_env() -> Self {
+        let requested = |name: &str| {
+            std::env::var("FOF_BACKEND")
+                .map(|v| v.eq_ignore_ascii_case(name))
+                .unwrap_or(false)
+                || std::env::args().any(|a| a == format!("--{}-backend", name))
+        };
+
+        if requested("fake") {
+            let data_dir = std::env::var("FOF_FAKE_DATA").unwrap_or("testdata".to_string());
+            BackendKind::Fake(PathBuf::from(data_dir))
+        } else if requested("native") {
+            BackendKind::Native
+        } else {
+            BackendKind::Python
+        }
+    }
+}
+
+static BACKEND: OnceLock<Box<dyn InferenceBackend>> = OnceLock::new();
+
+/// Muss vor dem ersten `backend()` Aufruf passieren, sonst wird Python benutzt.
+pub fn init_backend(kind: BackendKind) {
+    let backend: Box<dyn InferenceBackend> = match kind {
+        BackendKind::Python => Box::new(PythonBackend),
+        BackendKind::Native => Box::new(native_inference::NativeBackend),
+        BackendKind::Fake(data_dir) => Box::new(FakeBackend::new(data_dir)),
+    };
+
+    if BACKEND.set(backend).is_err() {
+        eprintln!("Backend wurde schon gesetzt, ignoriere init_backend");
+    }
+}
+
+pub fn backend() -> &'static dyn InferenceBackend {
+    BACKEND.get_or_init(|| Box::new(PythonBackend)).as_ref()
+}
+
+// So viele Zeilen von stderr landen im PythonError
+const PYTHON_ERROR_LINES: usize = 15;
+
+fn start_python(args: Vec<&str>) -> Result<String, FofError> {
+    match Command::new("python").args(args).output() {
+        Ok(output) if output.status.success() => {
+            Ok(String::from_utf8_lossy(&output.stdout).to_string())
+        }
+        Ok(output) => {
+            // Tracebacks von ultralytics sind lang, der Fehler steht immer am Ende
+            let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+            let lines: Vec<&str> = stderr.lines().collect();
+            let tail = lines[lines.len().saturating_sub(PYTHON_ERROR_LINES)..].join("\n");
+            Err(FofError::PythonError(tail))
+        }
+        Err(e) => Err(FofError::FailedToStartPython),
+    }
+}
+
+/// Wie `start_python`, aber stdout + stderr landen in `log` (siehe `run_log`).
+pub fn start_python_logged(
+    args: Vec<&str>,
+    log: File,
+    log_path: &Path,
+) -> Result<String, FofError> {
+    let log_err = log.try_clone()?;
+
+    let status = Command::new("python")
+        .args(args)
+        .env("PYTHONUNBUFFERED", "1")
+        .stdout(Stdio::from(log))
+        .stderr(Stdio::from(log_err))
+        .status()
+        .map_err(|_| FofError::FailedToStartPython)?;
+
+    if !status.success() {
+        return Err(FofError::PythonError(run_log::tail(
+            log_path,
+            PYTHON_ERROR_LINES,
+        )));
+    }
+
+    Ok(fs::read_to_string(log_path).unwrap_or_default())
+}
+
+fn read_buildings<P: AsRef<Path>>(path: P) -> Result<Vec<Building>, FofError> {
+    let file = File::open(path).map_err(|e| FofError::FailedReadingFile(e.to_string()))?;
+
+    let reader = BufReader::new(file);
+
+    serde_json::from_reader(reader).map_err(|e| FofError::FailedReadingFile(e.to_string()))
+}
+
+/// Die bisherige Brücke: `src/image_data.py` mit ultralytics.
+pub struct PythonBackend;
+
+impl InferenceBackend for PythonBackend {
+    fn name(&self) -> &'static str {
+        "Python (ultralytics)"
+    }
+
+    fn predict(&self, model_name: &str, screenshot_path: &str) -> Result<Vec<Building>, FofError> {
+        // Eigener Ordner pro Anfrage, damit sich parallele Vorhersagen nicht überschreiben.
+        // Wird am Ende der Funktion automatisch gelöscht.
+        let workspace = communication::Workspace::create(model_name)?;
+
+        let target_screenshot_path = workspace.file("screenshot.png");
+
+        fs::copy(screenshot_path, &target_screenshot_path).map_err(|e| {
+            FofError::FailedToCopyData(format!(
+                "Screenshot {} konnte nicht nach {} kopiert werden: {}",
+                screenshot_path,
+                workspace.path().display(),
+                e
+            ))
+        })?;
+
+        let data_path = workspace.file("data.json");
+
+        inference_server::with_server(model_name, |server| {
+            server.predict(&target_screenshot_path, &data_path)
+        })?;
+
+        if !check_if_exists(&data_path)? {
+            return Err(FofError::FailedReadingFile(
+                format!("data.json in {} nicht gefunden", data_path).to_string(),
+            ));
+        }
+
+        read_buildings(data_path)
+    }
+
+    fn check_base_weights(&self, yolo_model: &YoloModel) -> Result<(), FofError> {
+        // Eingebaute Modelle ohne lokale Datei lädt ultralytics selbst herunter
+        if !yolo_model.is_local() {
+            if let YoloModel::Custom(_) = yolo_model {
+                return Err(FofError::YoloModelNotFound);
+            }
+            return Ok(());
+        }
+
+        let weights = yolo_model.weights_arg();
+        start_python(vec![
+            "src/image_data.py",
+            "--check-weights",
+            "--base",
+            &weights,
+        ])?;
+
+        Ok(())
+    }
+
+    fn create_model(
+        &self,
+        model_name: &str,
+        dataset_type: &DatasetType,
+        yolo_model: &YoloModel,
+        options: &CreateModelOptions,
+    ) -> Result<(), FofError> {
+        let data_yaml = dataset_type.data_yaml();
+        let yolo_model_string = yolo_model.weights_arg();
+        let epochs = options.epochs.to_string();
+        let imgsz = options.imgsz.to_string();
+
+        let args = vec![
+            "src/image_data.py",
+            "--create-model",
+            "--base",
+            yolo_model_string.as_str(),
+            "--model-name",
+            model_name,
+            "--data",
+            data_yaml.as_str(),
+            "--epochs",
+            epochs.as_str(),
+            "--imgsz",
+            imgsz.as_str(),
+        ];
+
+        // Das Log darf den Modellordner nicht vorher anlegen (siehe `run_log::PENDING_LOG_DIR`)
+        let (log, log_path) = run_log::create_pending_log(model_name, RunKind::Create)?;
+        start_python_logged(args, log, &log_path)?;
+        run_log::adopt_log(model_name, &log_path)?;
+
+        Ok(())
+    }
+
+    fn train(
+        &self,
+        model_name: &str,
+        dataset_type: &DatasetType,
+        epochen: i32,
+        config: &TrainingConfig,
+        resume: bool,
+    ) -> Result<Option<Child>, FofError> {
+        let (log, _) = run_log::create_log(model_name, RunKind::Train)?;
+        let log_err = log.try_clone()?;
+
+        let child = Command::new("python")
+            .arg("src/image_data.py")
+            .arg("--train")
+            .args(if resume { vec!["--resume"] } else { vec![] })
+            .arg("--model-name")
+            .arg(model_name)
+            .arg("--epochs")
+            .arg(epochen.to_string())
+            .arg("--data")
+            .arg(dataset_type.data_yaml())
+            .arg("--config")
+            .arg(TrainingConfig::path(model_name))
+            // Output wird in der UI angezeigt (siehe training_progress)
+            .env("PYTHONUNBUFFERED", "1")
+            .stdout(Stdio::from(log))
+            .stderr(Stdio::from(log_err))
+            .spawn()
+            .map_err(|e| {
+                eprintln!("Fehler beim Starten des Trainingsprozesses: {}", e);
+                FofError::FailedToStartPython
+            })?;
+
+        Ok(Some(child))
+    }
+
+    fn validate(&self, model_name: &str, dataset_type: &DatasetType) -> Result<(), FofError> {
+        let data_yaml = dataset_type.data_yaml();
+        let args = vec![
+            "src/image_data.py",
+            "--testvals",
+            "--model-name",
+            model_name,
+            "--data",
+            data_yaml.as_str(),
+        ];
+        let (log, log_path) = run_log::create_log(model_name, RunKind::Testvals)?;
+        let python_output = start_python_logged(args, log, &log_path)?;
+
+        Ok(())
+    }
+}
+
+// Gleiche Spalten wie die results.csv von ultralytics
+const RESULTS_HEADER: &str = "epoch,train/box_loss,train/cls_loss,train/dfl_loss,metrics/precision(B),metrics/recall(B),metrics/mAP50(B),metrics/mAP50-95(B),val/box_loss,val/cls_loss,val/dfl_loss,lr/pg0,lr/pg1,lr/pg2";
+
+/// Backend ohne Python: Vorhersagen kommen aus aufgenommenen `data.json` Dateien,
+/// Training schreibt nur ausgedachte Metriken in `results.csv`.
+///
+/// Für ein Bild `foo.png` wird zuerst `<data_dir>/foo.json` gesucht, sonst `<data_dir>/data.json`.
+pub struct FakeBackend {
+    data_dir: PathBuf,
+}
+
+impl FakeBackend {
+    pub fn new(data_dir: PathBuf) -> Self {
+        FakeBackend { data_dir }
+    }
+
+    fn fake_metrics_row(epoch: usize) -> String {
+        // Kurven, die mit den Epochen langsam besser werden
+        let progress = 1.0 - 0.9_f64.powi(epoch as i32);
+        let loss = 2.0 - 1.5 * progress;
+
+        format!(
+            "{},{:.5},{:.5},{:.5},{:.5},{:.5},{:.5},{:.5},{:.5},{:.5},{:.5},{:.6},{:.6},{:.6}",
+            epoch,
+            loss,
+            loss * 1.2,
+            loss * 0.9,
+            0.3 + 0.6 * progress,
+            0.25 + 0.6 * progress,
+            0.2 + 0.7 * progress,
+            0.1 + 0.6 * progress,
+            loss * 1.1,
+            loss * 1.3,
+            loss,
+            0.01,
+            0.01,
+            0.01,
+        )
+    }
+
+    fn append_epochs(model_name: &str, epochen: usize) -> Result<(), FofError> {
+        let path = format!("runs/detect/{}/results.csv", model_name);
+
+        let existing = fs::read_to_string(&path).unwrap_or_default();
+        let done = existing.lines().skip(1).count();
+
+        let mut file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)?;
+
+        if existing.is_empty() {
+            writeln!(file, "{}", RESULTS_HEADER)?;
+        }
+
+        for epoch in done + 1..=done + epochen {
+            writeln!(file, "{}", FakeBackend::fake_metrics_row(epoch))?;
+        }
+
+        Ok(())
+    }
+}
+
+impl InferenceBackend for FakeBackend {
+    fn name(&self) -> &'static str {
+        "Fake (aufgenommene Daten)"
+    }
+
+    fn predict(&self, model_name: &str, screenshot_path: &str) -> Result<Vec<Building>, FofError> {
+        let stem = Path::new(screenshot_path)
+            .file_stem()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_string();
+
+        let per_image = self.data_dir.join(format!("{}.json", stem));
+        let fallback = self.data_dir.join("data.json");
+
+        if per_image.exists() {
+            read_buildings(per_image)
+        } else if fallback.exists() {
+            read_buildings(fallback)
+        } else {
+            Err(FofError::FailedReadingFile(format!(
+                "Keine aufgenommenen Daten in {} gefunden",
+                self.data_dir.display()
+            )))
+        }
+    }
+
+    fn check_base_weights(&self, yolo_model: &YoloModel) -> Result<(), FofError> {
+        match yolo_model {
+            YoloModel::Custom(path) if !path.exists() => Err(FofError::YoloModelNotFound),
+            _ => Ok(()),
+        }
+    }
+
+    fn create_model(
+        &self,
+        model_name: &str,
+        dataset_type: &DatasetType,
+        yolo_model: &YoloModel,
+        options: &CreateModelOptions,
+    ) -> Result<(), FofError> {
+        let model_path = format!("runs/detect/{}", model_name);
+        fs::create_dir_all(format!("{}/weights", model_path))?;
+
+        fs::write(
+            format!("{}/args.yaml", model_path),
+            format!(
+                "model: {}\ndata: {}\nepochs: {}\nimgsz: {}\n",
+                yolo_model.weights_arg(),
+                dataset_type.data_yaml(),
+                options.epochs,
+                options.imgsz
+            ),
+        )?;
+        fs::write(format!("{}/weights/best.pt", model_path), "")?;
+        fs::write(format!("{}/weights/last.pt", model_path), "")?;
+
+        FakeBackend::append_epochs(model_name, options.epochs as usize)
+    }
+
+    fn train(
+        &self,
+        model_name: &str,
+        dataset_type: &DatasetType,
+        epochen: i32,
+        config: &TrainingConfig,
+        resume: bool,
+    ) -> Result<Option<Child>, FofError> {
+        FakeBackend::append_epochs(model_name, epochen.max(0) as usize)?;
+
+        // Damit der Log-Viewer auch ohne Python etwas zeigt
+        let (mut log, _) = run_log::create_log(model_name, RunKind::Train)?;
+        writeln!(log, "Fake Training: {} Epochen", epochen)?;
+
+        Ok(None)
+    }
+
+    fn validate(&self, model_name: &str, dataset_type: &DatasetType) -> Result<(), FofError> {
+        let path = class_metrics_path(model_name);
+        if let Some(dir) = Path::new(&path).parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        // Klassennamen aus dem Datensatz, sonst ein paar Platzhalter
+        let names = read_class_names(dataset_type)
+            .unwrap_or_else(|_| (0..5).map(|i| (i, format!("klasse_{}", i))).collect());
+
+        let mut file = File::create(&path)?;
+        writeln!(
+            file,
+            "class,images,instances,precision,recall,mAP50,mAP50-95"
+        )?;
+        for (i, name) in names {
+            // Ein paar Klassen absichtlich schlecht, damit man in der UI was sieht
+            let quality = 0.3 + 0.65 * ((i * 37 % 11) as f64 / 10.0);
+            writeln!(
+                file,
+                "{},{},{},{:.5},{:.5},{:.5},{:.5}",
+                name,
+                5 + i * 3 % 20,
+                10 + i * 7 % 60,
+                quality,
+                quality * 0.95,
+                quality * 0.9,
+                quality * 0.6
+            )?;
+        }
+
+        Ok(())
+    }
+}
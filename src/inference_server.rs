@@ -0,0 +1,245 @@
+use crate::prelude::*;
+use crate::run_log::RunKind;
+
+use std::io::BufRead;
+use std::process::{ChildStdin, Stdio};
+use std::sync::mpsc::{channel, Receiver, RecvTimeoutError};
+use std::sync::OnceLock;
+use std::time::Duration;
+
+// Das erste Laden von ultralytics + best.pt kann dauern, danach sollte alles schnell gehen
+const STARTUP_TIMEOUT: Duration = Duration::from_secs(180);
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Deserialize)]
+struct ServerResponse {
+    id: Option<u64>,
+    ok: bool,
+    error: Option<String>,
+}
+
+/// Ein dauerhaft laufender `image_data.py --serve` Prozess für genau ein Modell.
+/// Anfragen und Antworten sind jeweils eine JSON-Zeile über stdin/stdout.
+pub struct InferenceServer {
+    model_name: String,
+    child: Child,
+    stdin: ChildStdin,
+    lines: Receiver<String>,
+    next_id: u64,
+}
+
+impl InferenceServer {
+    pub fn spawn(model_name: &str) -> Result<Self, FofError> {
+        // stderr geht direkt ins Log, stdout wird unten mitgeschrieben
+        let (mut log, _) = run_log::create_log(model_name, RunKind::Predict)?;
+        let log_err = log.try_clone()?;
+
+        let mut child = Command::new("python")
+            .arg("src/image_data.py")
+            .arg("--serve")
+            .arg("--model-name")
+            .arg(model_name)
+            .env("PYTHONUNBUFFERED", "1")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::from(log_err))
+            .spawn()
+            .map_err(|_| FofError::FailedToStartPython)?;
+
+        let stdin = child
+            .stdin
+            .take()
+            .ok_or(FofError::InferenceServerError("stdin fehlt".to_string()))?;
+        let stdout = child
+            .stdout
+            .take()
+            .ok_or(FofError::InferenceServerError("stdout fehlt".to_string()))?;
+
+        // Eigener Thread fürs Lesen, damit wir beim Warten ein Timeout haben
+        let (tx, lines) = channel();
+        thread::spawn(move || {
+            for line in BufReader::new(stdout).lines() {
+                let Ok(line) = line else { break };
+                let _ = writeln!(log, "{}", line);
+                if tx.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+
+        let mut server = InferenceServer {
+            model_name: model_name.to_string(),
+            child,
+            stdin,
+            lines,
+            next_id: 1,
+        };
+
+        // Der Server meldet sich mit id 0, sobald das Modell geladen ist
+        server.wait_for(0, STARTUP_TIMEOUT)?;
+
+        Ok(server)
+    }
+
+    pub fn is_alive(&mut self) -> bool {
+        matches!(self.child.try_wait(), Ok(None))
+    }
+
+    fn wait_for(&mut self, id: u64, timeout: Duration) -> Result<(), FofError> {
+        let deadline = std::time::Instant::now() + timeout;
+
+        loop {
+            let remaining = deadline.saturating_duration_since(std::time::Instant::now());
+
+            let line = match self.lines.recv_timeout(remaining) {
+                Ok(line) => line,
+                Err(RecvTimeoutError::Timeout) => {
+                    return Err(FofError::InferenceServerError(format!(
+                        "Keine Antwort von {} nach {:?}",
+                        self.model_name, timeout
+                    )))
+                }
+                Err(RecvTimeoutError::Disconnected) => {
+                    return Err(FofError::InferenceServerError(format!(
+                        "Server für {} ist abgestürzt",
+                        self.model_name
+                    )))
+                }
+            };
+
+            // Alles was kein JSON ist (z. B. Logs von ultralytics) wird ignoriert
+            let Ok(response) = serde_json::from_str::<ServerResponse>(&line) else {
+                continue;
+            };
+
+            if response.id != Some(id) {
+                continue;
+            }
+
+            if response.ok {
+                return Ok(());
+            }
+
+            return Err(FofError::PythonError(
+                response
+                    .error
+                    .unwrap_or_else(|| "Unbekannter Fehler".to_string()),
+            ));
+        }
+    }
+
+    fn request(&mut self, mut request: serde_json::Value) -> Result<(), FofError> {
+        let id = self.next_id;
+        self.next_id += 1;
+        request["id"] = serde_json::json!(id);
+
+        writeln!(self.stdin, "{}", request)
+            .and_then(|_| self.stdin.flush())
+            .map_err(|e| FofError::InferenceServerError(e.to_string()))?;
+
+        self.wait_for(id, REQUEST_TIMEOUT)
+    }
+
+    pub fn ping(&mut self) -> Result<(), FofError> {
+        self.request(serde_json::json!({ "cmd": "ping" }))
+    }
+
+    pub fn reload(&mut self) -> Result<(), FofError> {
+        self.request(serde_json::json!({ "cmd": "reload" }))
+    }
+
+    pub fn predict(&mut self, image_path: &str, output_path: &str) -> Result<(), FofError> {
+        self.request(serde_json::json!({
+            "cmd": "predict",
+            "image": image_path,
+            "output": output_path,
+        }))
+    }
+
+    fn shutdown(&mut self) {
+        let _ = self.request(serde_json::json!({ "cmd": "shutdown" }));
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+impl Drop for InferenceServer {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+/// Server für ein Modell. `stale` wird ohne Lock gesetzt, damit die UI nie auf einen
+/// startenden Server warten muss, neu geladen wird erst bei der nächsten Anfrage.
+#[derive(Default)]
+struct ServerSlot {
+    server: Mutex<Option<InferenceServer>>,
+    stale: AtomicBool,
+}
+
+fn servers() -> &'static Mutex<HashMap<String, Arc<ServerSlot>>> {
+    static SERVERS: OnceLock<Mutex<HashMap<String, Arc<ServerSlot>>>> = OnceLock::new();
+    SERVERS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+fn slot_for(model_name: &str) -> Arc<ServerSlot> {
+    let mut servers = servers().lock().unwrap();
+    servers.entry(model_name.to_string()).or_default().clone()
+}
+
+/// Führt `f` mit dem Server des Modells aus. Ist der Server noch nicht gestartet oder
+/// abgestürzt, wird er (neu) gestartet und die Anfrage einmal wiederholt.
+pub fn with_server<T>(
+    model_name: &str,
+    mut f: impl FnMut(&mut InferenceServer) -> Result<T, FofError>,
+) -> Result<T, FofError> {
+    let slot = slot_for(model_name);
+    let mut server = slot.server.lock().unwrap();
+
+    // Neue Gewichte seit der letzten Anfrage (siehe `reload_model`)
+    if slot.stale.swap(false, Ordering::SeqCst) {
+        if server.as_mut().is_some_and(|s| s.is_alive()) {
+            if let Err(e) = server.as_mut().unwrap().reload() {
+                eprintln!(
+                    "Reload von {} fehlgeschlagen, starte neu: {:?}",
+                    model_name, e
+                );
+                *server = None;
+            }
+        }
+    }
+
+    if !server.as_mut().is_some_and(|s| s.is_alive()) {
+        *server = Some(InferenceServer::spawn(model_name)?);
+    }
+
+    match f(server.as_mut().unwrap()) {
+        Err(FofError::InferenceServerError(e)) => {
+            eprintln!("Inference Server für {} neu gestartet: {}", model_name, e);
+            *server = Some(InferenceServer::spawn(model_name)?);
+            f(server.as_mut().unwrap())
+        }
+        res => res,
+    }
+}
+
+pub fn ping(model_name: &str) -> Result<(), FofError> {
+    with_server(model_name, |s| s.ping())
+}
+
+/// Merkt sich, dass `best.pt` neu ist (z. B. nach einem Training). Blockiert nicht,
+/// ein laufender Server lädt die Gewichte vor der nächsten Anfrage neu.
+pub fn reload_model(model_name: &str) {
+    slot_for(model_name).stale.store(true, Ordering::SeqCst);
+}
+
+pub fn shutdown(model_name: &str) {
+    let slot = servers().lock().unwrap().remove(model_name);
+
+    if let Some(slot) = slot {
+        if let Some(mut server) = slot.server.lock().unwrap().take() {
+            server.shutdown();
+        }
+    }
+}
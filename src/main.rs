@@ -3,14 +3,26 @@
 use crate::prelude::*;
 
 mod bot_actions;
+mod class_thresholds;
+mod communication;
 mod debug;
+mod ensemble;
 mod filter_buildings;
+mod folder_settings;
 mod image_data_wrapper;
+mod inference_backend;
+mod inference_server;
+mod native_inference;
+mod prediction_cache;
 mod prelude;
+mod run_log;
 mod screener;
 mod settings_manager;
 mod split_image;
+mod sweep;
 mod threading;
+mod training_progress;
+mod training_queue;
 mod ui;
 mod walls;
 
@@ -39,6 +51,8 @@ pub enum FofError {
     NoStatsFound(String), // model name
     NoTrainingRunning,
     FailedToStopTraining,
+    InferenceServerError(String), // Server Prozess antwortet nicht oder ist abgestürzt
+    OnnxError(String),            // Fehler beim Laden/Ausführen vom ONNX Modell in Rust
 }
 
 impl From<io::Error> for FofError {
@@ -0,0 +1,297 @@
+use crate::image_data_wrapper::{
+    self, Building, CreateModelOptions, DatasetType, TrainingConfig, YoloModel,
+};
+use crate::inference_backend::{InferenceBackend, PythonBackend};
+use crate::prelude::*;
+use crate::run_log::RunKind;
+
+use image::imageops::FilterType;
+use std::sync::OnceLock;
+use tract_onnx::prelude::*;
+
+// Gleiche Eingabegröße wie beim Export (imgsz=640)
+const INPUT_SIZE: u32 = 640;
+// Wie ultralytics beim Validieren, damit wir ähnlich viele Boxen wie Python bekommen
+const MIN_CONFIDENCE: f32 = 0.001;
+const NMS_IOU: f32 = 0.7;
+const MAX_DETECTIONS: usize = 300;
+
+type OnnxPlan = TypedRunnableModel<TypedModel>;
+
+/// Ein in Rust geladener ONNX-Export von `best.pt` inklusive Klassennamen.
+pub struct NativeModel {
+    plan: OnnxPlan,
+    class_names: HashMap<u32, String>,
+}
+
+pub fn onnx_path(model_name: &str) -> String {
+    format!("runs/detect/{}/weights/best.onnx", model_name)
+}
+
+/// `false`, wenn es kein `best.onnx` gibt oder es älter als `best.pt` ist (neu trainiert).
+pub fn has_onnx_export(model_name: &str) -> bool {
+    let modified = |p: String| fs::metadata(p).and_then(|m| m.modified()).ok();
+
+    match (
+        modified(onnx_path(model_name)),
+        modified(format!("runs/detect/{}/weights/best.pt", model_name)),
+    ) {
+        (Some(onnx), Some(pt)) => onnx >= pt,
+        (Some(_), None) => true,
+        _ => false,
+    }
+}
+
+fn onnx_err(e: impl Display) -> FofError {
+    FofError::OnnxError(e.to_string())
+}
+
+impl NativeModel {
+    pub fn load(model_name: &str) -> Result<Self, FofError> {
+        let path = onnx_path(model_name);
+        if !Path::new(&path).exists() {
+            return Err(FofError::FailedReadingFile(path));
+        }
+
+        let plan = tract_onnx::onnx()
+            .model_for_path(&path)
+            .and_then(|m| {
+                m.with_input_fact(
+                    0,
+                    f32::fact([1, 3, INPUT_SIZE as usize, INPUT_SIZE as usize]).into(),
+                )
+            })
+            .and_then(|m| m.into_optimized())
+            .and_then(|m| m.into_runnable())
+            .map_err(onnx_err)?;
+
+        let dataset_type = image_data_wrapper::get_dataset_type(model_name)?;
+        let class_names = image_data_wrapper::read_class_names(&dataset_type)?;
+
+        Ok(NativeModel {
+            plan,
+            class_names: class_names.into_iter().collect(),
+        })
+    }
+
+    pub fn predict<P: AsRef<Path>>(&self, image_path: P) -> Result<Vec<Building>, FofError> {
+        let image = image::open(image_path.as_ref())
+            .map_err(|e| FofError::FailedReadingFile(e.to_string()))?
+            .to_rgb8();
+
+        // Letterbox wie bei ultralytics: Seitenverhältnis behalten, Rest grau (114) auffüllen
+        let (width, height) = image.dimensions();
+        let scale = (INPUT_SIZE as f32 / width as f32).min(INPUT_SIZE as f32 / height as f32);
+        let new_w = ((width as f32 * scale).round() as u32).max(1);
+        let new_h = ((height as f32 * scale).round() as u32).max(1);
+        let pad_x = (INPUT_SIZE - new_w) / 2;
+        let pad_y = (INPUT_SIZE - new_h) / 2;
+
+        let resized = image::imageops::resize(&image, new_w, new_h, FilterType::Triangle);
+
+        let size = INPUT_SIZE as usize;
+        let input: Tensor =
+            tract_ndarray::Array4::from_shape_fn((1, 3, size, size), |(_, c, y, x)| {
+                let (x, y) = (x as u32, y as u32);
+                if x < pad_x || y < pad_y || x >= pad_x + new_w || y >= pad_y + new_h {
+                    114.0 / 255.0
+                } else {
+                    resized.get_pixel(x - pad_x, y - pad_y)[c] as f32 / 255.0
+                }
+            })
+            .into();
+
+        let outputs = self.plan.run(tvec!(input.into())).map_err(onnx_err)?;
+        let output = outputs[0].to_array_view::<f32>().map_err(onnx_err)?;
+
+        let shape = output.shape().to_vec();
+        if shape.len() != 3 {
+            return Err(FofError::OnnxError(format!(
+                "Unerwartete Output-Form {:?}",
+                shape
+            )));
+        }
+
+        // YOLOv8 exportiert [1, 4 + Klassen, Anker], manche Exporte sind transponiert
+        let transposed = shape[1] > shape[2];
+        let (num_values, num_anchors) = if transposed {
+            (shape[2], shape[1])
+        } else {
+            (shape[1], shape[2])
+        };
+        // Mindestens die 4 Box-Werte und eine Klasse
+        if num_values < 5 || shape[0] != 1 {
+            return Err(FofError::OnnxError(format!(
+                "Unerwartete Output-Form {:?}",
+                shape
+            )));
+        }
+
+        let value = |anchor: usize, idx: usize| {
+            if transposed {
+                output[[0, anchor, idx]]
+            } else {
+                output[[0, idx, anchor]]
+            }
+        };
+
+        let mut detections = vec![];
+        for anchor in 0..num_anchors {
+            let mut best_class = 0;
+            let mut best_score = f32::MIN;
+            for class in 0..num_values - 4 {
+                let score = value(anchor, 4 + class);
+                if score > best_score {
+                    best_score = score;
+                    best_class = class;
+                }
+            }
+
+            if best_score < MIN_CONFIDENCE {
+                continue;
+            }
+
+            let (cx, cy, w, h) = (
+                value(anchor, 0),
+                value(anchor, 1),
+                value(anchor, 2),
+                value(anchor, 3),
+            );
+
+            // zurück in Pixel vom Original-Screenshot
+            let to_original_x = |v: f32| ((v - pad_x as f32) / scale).clamp(0.0, width as f32);
+            let to_original_y = |v: f32| ((v - pad_y as f32) / scale).clamp(0.0, height as f32);
+
+            detections.push(Building {
+                class_id: best_class as i32,
+                class_name: self
+                    .class_names
+                    .get(&(best_class as u32))
+                    .cloned()
+                    .unwrap_or_else(|| best_class.to_string()),
+                confidence: best_score,
+                bounding_box: (
+                    to_original_x(cx - w / 2.0),
+                    to_original_y(cy - h / 2.0),
+                    to_original_x(cx + w / 2.0),
+                    to_original_y(cy + h / 2.0),
+                ),
+            });
+        }
+
+        Ok(non_max_suppression(detections, NMS_IOU, MAX_DETECTIONS))
+    }
+}
+
+/// Klassenweise NMS wie in ultralytics (`agnostic=False`).
+fn non_max_suppression(detections: Vec<Building>, iou: f32, max_det: usize) -> Vec<Building> {
+    let mut kept = filter_buildings::nms(&detections, filter_buildings::NmsMode::ClassAware, iou);
+    kept.truncate(max_det);
+    kept
+}
+
+fn loaded_models() -> &'static Mutex<HashMap<String, Arc<NativeModel>>> {
+    static MODELS: OnceLock<Mutex<HashMap<String, Arc<NativeModel>>>> = OnceLock::new();
+    MODELS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Lädt das ONNX Modell beim ersten Aufruf und benutzt es danach wieder.
+pub fn get_native_prediction<P>(
+    model_name: &str,
+    screenshot_path: &P,
+) -> Result<Vec<Building>, FofError>
+where
+    P: AsRef<Path> + Debug + Display,
+{
+    let cached = loaded_models().lock().unwrap().get(model_name).cloned();
+
+    let model = match cached {
+        Some(model) => model,
+        None => {
+            let model = Arc::new(NativeModel::load(model_name)?);
+            loaded_models()
+                .lock()
+                .unwrap()
+                .insert(model_name.to_string(), model.clone());
+            model
+        }
+    };
+
+    model.predict(screenshot_path)
+}
+
+/// Nach Training oder neuem Export muss das Modell neu geladen werden.
+pub fn forget_model(model_name: &str) {
+    loaded_models().lock().unwrap().remove(model_name);
+}
+
+/// Exportiert `best.pt` mit ultralytics nach `best.onnx` (einmalig, braucht Python).
+pub fn export_onnx(model_name: &str) -> Result<(), FofError> {
+    let (log, log_path) = run_log::create_log(model_name, RunKind::Export)?;
+    inference_backend::start_python_logged(
+        vec![
+            "src/image_data.py",
+            "--export-onnx",
+            "--model-name",
+            model_name,
+        ],
+        log,
+        &log_path,
+    )?;
+
+    forget_model(model_name);
+    Ok(())
+}
+
+pub const BACKEND_NAME: &str = "Nativ (ONNX)";
+
+/// Vorhersagen ohne Python über `best.onnx`. Alles andere (Training, TestVals, ...)
+/// braucht weiter ultralytics und geht an `PythonBackend`.
+pub struct NativeBackend;
+
+impl InferenceBackend for NativeBackend {
+    fn name(&self) -> &'static str {
+        BACKEND_NAME
+    }
+
+    fn predict(&self, model_name: &str, screenshot_path: &str) -> Result<Vec<Building>, FofError> {
+        // Nach einem Training ist best.onnx veraltet, ohne Python können wir nicht selbst exportieren
+        if !has_onnx_export(model_name) {
+            return Err(FofError::OnnxError(format!(
+                "{} hat kein aktuelles best.onnx, bitte zuerst exportieren",
+                model_name
+            )));
+        }
+
+        get_native_prediction(model_name, &screenshot_path)
+    }
+
+    fn check_base_weights(&self, yolo_model: &YoloModel) -> Result<(), FofError> {
+        PythonBackend.check_base_weights(yolo_model)
+    }
+
+    fn create_model(
+        &self,
+        model_name: &str,
+        dataset_type: &DatasetType,
+        yolo_model: &YoloModel,
+        options: &CreateModelOptions,
+    ) -> Result<(), FofError> {
+        PythonBackend.create_model(model_name, dataset_type, yolo_model, options)
+    }
+
+    fn train(
+        &self,
+        model_name: &str,
+        dataset_type: &DatasetType,
+        epochen: i32,
+        config: &TrainingConfig,
+        resume: bool,
+    ) -> Result<Option<Child>, FofError> {
+        PythonBackend.train(model_name, dataset_type, epochen, config, resume)
+    }
+
+    fn validate(&self, model_name: &str, dataset_type: &DatasetType) -> Result<(), FofError> {
+        PythonBackend.validate(model_name, dataset_type)
+    }
+}
@@ -0,0 +1,201 @@
+use crate::image_data_wrapper::Building;
+use crate::prelude::*;
+
+use sha2::{Digest, Sha256};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::SystemTime;
+
+// Ein Unterordner pro Modell, damit man den Cache für ein Modell einzeln löschen kann
+pub const CACHE_DIR: &str = "prediction_cache";
+
+// Wird beim Speichern eingehalten, die ältesten Einträge fliegen zuerst raus
+pub const MAX_CACHE_BYTES: u64 = 256 * 1024 * 1024;
+pub const MAX_CACHE_ENTRIES: usize = 5000;
+
+// Aufräumen liest den ganzen Ordner ein, deshalb nur bei jedem n-ten `store`
+const CLEANUP_EVERY: usize = 50;
+static STORES: AtomicUsize = AtomicUsize::new(0);
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CacheStats {
+    pub entries: usize,
+    pub bytes: u64,
+}
+
+struct CacheEntry {
+    path: PathBuf,
+    size: u64,
+    modified: SystemTime,
+}
+
+fn model_dir(model_name: &str) -> PathBuf {
+    Path::new(CACHE_DIR).join(model_name)
+}
+
+/// Ändert sich, sobald `best.pt` neu geschrieben wird (Training, Fork, ...).
+/// Mit dem Backend, damit Fake- und Python-Ergebnisse sich nicht mischen.
+fn weights_key(model_name: &str) -> Option<String> {
+    let metadata = fs::metadata(format!("runs/detect/{}/weights/best.pt", model_name)).ok()?;
+    let modified = metadata
+        .modified()
+        .ok()?
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .ok()?;
+
+    let mut hasher = Sha256::new();
+    hasher.update(inference_backend::backend().name().as_bytes());
+    hasher.update(modified.as_nanos().to_le_bytes());
+    hasher.update(metadata.len().to_le_bytes());
+    Some(short_hex(hasher.finalize().as_slice()))
+}
+
+// Über den Inhalt, nicht den Pfad: Screenshots werden oft unter dem gleichen Namen überschrieben
+fn image_key<P: AsRef<Path>>(image_path: P) -> Option<String> {
+    let bytes = fs::read(image_path).ok()?;
+    Some(short_hex(Sha256::digest(&bytes).as_slice()))
+}
+
+// SHA-256 statt `DefaultHasher`: der ist nicht über Rust-Versionen stabil, die Dateinamen
+// im Cache müssen aber auch nach einem Update noch passen. 16 Bytes reichen als Name.
+fn short_hex(digest: &[u8]) -> String {
+    digest[..16].iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn entry_path(model_name: &str, weights_key: &str, image_key: &str) -> PathBuf {
+    model_dir(model_name).join(format!("{}_{}.json", weights_key, image_key))
+}
+
+/// Gecachte Vorhersage, `None` wenn es keine passende gibt.
+pub fn get<P: AsRef<Path>>(model_name: &str, image_path: P) -> Option<Vec<Building>> {
+    let path = entry_path(
+        model_name,
+        &weights_key(model_name)?,
+        &image_key(image_path)?,
+    );
+    let file = File::open(&path).ok()?;
+    let buildings = serde_json::from_reader(BufReader::new(file)).ok()?;
+
+    // Treffer gelten als frisch, damit sie beim Aufräumen nicht zuerst rausfliegen
+    if let Ok(file) = File::options().write(true).open(&path) {
+        let _ = file.set_modified(SystemTime::now());
+    }
+
+    Some(buildings)
+}
+
+pub fn store<P: AsRef<Path>>(
+    model_name: &str,
+    image_path: P,
+    buildings: &[Building],
+) -> Result<(), FofError> {
+    let (Some(weights_key), Some(image_key)) = (weights_key(model_name), image_key(image_path))
+    else {
+        return Ok(());
+    };
+
+    let dir = model_dir(model_name);
+    fs::create_dir_all(&dir)?;
+
+    let json =
+        serde_json::to_string(buildings).map_err(|e| FofError::JsonParseError(e.to_string()))?;
+
+    // Erst in eine temporäre Datei, damit ein paralleles `get` nie eine halbe Datei liest
+    let path = entry_path(model_name, &weights_key, &image_key);
+    let tmp = path.with_extension(format!("tmp{}", std::process::id()));
+    fs::write(&tmp, json)?;
+    fs::rename(&tmp, &path)?;
+
+    // Beim ersten Speichern und danach bei jedem `CLEANUP_EVERY`-ten
+    if STORES.fetch_add(1, Ordering::Relaxed) % CLEANUP_EVERY == 0 {
+        remove_stale(&dir, &weights_key);
+        enforce_limits();
+    }
+    Ok(())
+}
+
+// Einträge von alten Gewichten braucht keiner mehr
+fn remove_stale(dir: &Path, weights_key: &str) {
+    for entry in entries_in(dir) {
+        let name = entry.path.file_name().unwrap_or_default().to_string_lossy();
+        if !name.starts_with(weights_key) {
+            let _ = fs::remove_file(&entry.path);
+        }
+    }
+}
+
+fn entries_in(dir: &Path) -> Vec<CacheEntry> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return vec![];
+    };
+
+    entries
+        .flatten()
+        .filter(|e| e.path().extension().map(|x| x == "json").unwrap_or(false))
+        .filter_map(|e| {
+            let metadata = e.metadata().ok()?;
+            Some(CacheEntry {
+                path: e.path(),
+                size: metadata.len(),
+                modified: metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH),
+            })
+        })
+        .collect()
+}
+
+fn all_entries() -> Vec<CacheEntry> {
+    let Ok(models) = fs::read_dir(CACHE_DIR) else {
+        return vec![];
+    };
+
+    models
+        .flatten()
+        .filter(|m| m.path().is_dir())
+        .flat_map(|m| entries_in(&m.path()))
+        .collect()
+}
+
+fn enforce_limits() {
+    let mut entries = all_entries();
+    let mut bytes: u64 = entries.iter().map(|e| e.size).sum();
+    let mut count = entries.len();
+
+    if bytes <= MAX_CACHE_BYTES && count <= MAX_CACHE_ENTRIES {
+        return;
+    }
+
+    entries.sort_by_key(|e| e.modified);
+    for entry in entries {
+        if bytes <= MAX_CACHE_BYTES && count <= MAX_CACHE_ENTRIES {
+            break;
+        }
+        if fs::remove_file(&entry.path).is_ok() {
+            bytes -= entry.size;
+            count -= 1;
+        }
+    }
+}
+
+pub fn stats() -> CacheStats {
+    let entries = all_entries();
+    CacheStats {
+        entries: entries.len(),
+        bytes: entries.iter().map(|e| e.size).sum(),
+    }
+}
+
+pub fn clear_model(model_name: &str) -> Result<(), FofError> {
+    let dir = model_dir(model_name);
+    if dir.exists() {
+        fs::remove_dir_all(&dir)
+            .map_err(|_| FofError::FailedDeletingDirectory(dir.display().to_string()))?;
+    }
+    Ok(())
+}
+
+pub fn clear_all() -> Result<(), FofError> {
+    if Path::new(CACHE_DIR).exists() {
+        fs::remove_dir_all(CACHE_DIR)
+            .map_err(|_| FofError::FailedDeletingDirectory(CACHE_DIR.to_string()))?;
+    }
+    Ok(())
+}
@@ -0,0 +1,242 @@
+use crate::prelude::*;
+
+use std::io::{Seek, SeekFrom};
+use std::time::SystemTime;
+
+// Unter runs/detect/<modell>/, damit die Logs mit dem Modell gelöscht / kopiert werden
+pub const LOG_DIR: &str = "logs";
+
+// Ältere Logs werden beim Anlegen eines neuen gelöscht
+pub const MAX_LOGS_PER_MODEL: usize = 50;
+
+// Beim Anlegen eines Modells darf es runs/detect/<modell>/ noch nicht geben,
+// sonst weicht ultralytics auf <modell>2 aus. Das Log kommt erst danach zum Modell.
+pub const PENDING_LOG_DIR: &str = "runs/pending_logs";
+
+/// Welcher Python Aufruf das Log geschrieben hat. Steht im Dateinamen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, EnumIter)]
+pub enum RunKind {
+    Train,
+    Create,
+    Testvals,
+    Predict,
+    Export,
+}
+
+impl ToString for RunKind {
+    fn to_string(&self) -> String {
+        match self {
+            RunKind::Train => "train".to_string(),
+            RunKind::Create => "create".to_string(),
+            RunKind::Testvals => "testvals".to_string(),
+            RunKind::Predict => "predict".to_string(),
+            RunKind::Export => "export".to_string(),
+        }
+    }
+}
+
+impl RunKind {
+    fn from_file_name(name: &str) -> Option<RunKind> {
+        let stem = name.strip_suffix(".log")?;
+        RunKind::iter().find(|k| stem.ends_with(&format!("_{}", k.to_string())))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RunLogEntry {
+    pub path: PathBuf,
+    pub kind: Option<RunKind>,
+    pub modified: SystemTime,
+    pub size: u64,
+}
+
+impl RunLogEntry {
+    pub fn file_name(&self) -> String {
+        self.path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default()
+    }
+}
+
+pub fn log_dir(model_name: &str) -> PathBuf {
+    Path::new("runs/detect").join(model_name).join(LOG_DIR)
+}
+
+/// Legt ein neues Log für einen Python Aufruf an, z. B. `20250101_120000_train.log`.
+pub fn create_log(model_name: &str, kind: RunKind) -> Result<(File, PathBuf), FofError> {
+    let dir = log_dir(model_name);
+    fs::create_dir_all(&dir)?;
+    prune_logs(model_name);
+
+    new_log_in(&dir, kind)
+}
+
+/// Wie `create_log`, aber außerhalb vom Modellordner (für `--create-model`).
+/// Nach Erfolg mit `adopt_log` zum Modell verschieben, sonst bleibt es für die Fehlersuche liegen.
+pub fn create_pending_log(model_name: &str, kind: RunKind) -> Result<(File, PathBuf), FofError> {
+    let dir = Path::new(PENDING_LOG_DIR).join(model_name);
+    fs::create_dir_all(&dir)?;
+
+    new_log_in(&dir, kind)
+}
+
+pub fn adopt_log(model_name: &str, pending: &Path) -> Result<PathBuf, FofError> {
+    let dir = log_dir(model_name);
+    fs::create_dir_all(&dir)?;
+
+    let target = dir.join(pending.file_name().unwrap_or_default());
+    fs::rename(pending, &target)?;
+
+    if let Some(parent) = pending.parent() {
+        let _ = fs::remove_dir(parent); // nur wenn leer
+    }
+    Ok(target)
+}
+
+fn new_log_in(dir: &Path, kind: RunKind) -> Result<(File, PathBuf), FofError> {
+    let stamp = Local::now().format("%Y%m%d_%H%M%S");
+    let mut path = dir.join(format!("{}_{}.log", stamp, kind.to_string()));
+
+    // Zwei Aufrufe in der gleichen Sekunde (z. B. mehrere Vorhersagen)
+    let mut i = 1;
+    while path.exists() {
+        path = dir.join(format!("{}_{}_{}.log", stamp, i, kind.to_string()));
+        i += 1;
+    }
+
+    let file = File::create(&path)?;
+    Ok((file, path))
+}
+
+/// Alle Logs von einem Modell, das neueste zuerst.
+pub fn list_logs(model_name: &str) -> Vec<RunLogEntry> {
+    let Ok(entries) = fs::read_dir(log_dir(model_name)) else {
+        return vec![];
+    };
+
+    let mut logs: Vec<RunLogEntry> = entries
+        .flatten()
+        .filter_map(|e| {
+            let name = e.file_name().to_string_lossy().to_string();
+            if !name.ends_with(".log") {
+                return None;
+            }
+
+            let metadata = e.metadata().ok()?;
+            Some(RunLogEntry {
+                path: e.path(),
+                kind: RunKind::from_file_name(&name),
+                modified: metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH),
+                size: metadata.len(),
+            })
+        })
+        .collect();
+
+    // Der Zeitstempel im Namen sortiert richtig, auch wenn die Dateien kopiert wurden
+    logs.sort_by(|a, b| b.file_name().cmp(&a.file_name()));
+    logs
+}
+
+pub fn latest_log(model_name: &str, kind: RunKind) -> Option<PathBuf> {
+    list_logs(model_name)
+        .into_iter()
+        .find(|l| l.kind == Some(kind))
+        .map(|l| l.path)
+}
+
+fn prune_logs(model_name: &str) {
+    for old in list_logs(model_name)
+        .into_iter()
+        .skip(MAX_LOGS_PER_MODEL - 1)
+    {
+        let _ = fs::remove_file(old.path);
+    }
+}
+
+/// Die letzten `lines` Zeilen aus einem Log, z. B. für Fehlermeldungen.
+pub fn tail<P: AsRef<Path>>(path: P, lines: usize) -> String {
+    let all = read_lines(path);
+    all[all.len().saturating_sub(lines)..].join("\n")
+}
+
+/// Log zum Anzeigen: ohne ANSI-Farben und bei tqdm-Balken (`\r`) nur der letzte Stand pro Zeile.
+pub fn read_lines<P: AsRef<Path>>(path: P) -> Vec<String> {
+    let mut reader = LogReader::new(path.as_ref().to_path_buf());
+    reader.update();
+    reader.lines
+}
+
+fn clean_line(bytes: &[u8]) -> String {
+    let text = String::from_utf8_lossy(bytes);
+    let last = text
+        .trim_end_matches('\r')
+        .rsplit('\r')
+        .next()
+        .unwrap_or_default();
+    training_progress::ansi_regex()
+        .replace_all(last, "")
+        .to_string()
+}
+
+/// Liest ein Log stückweise mit, bei jedem `update` nur das, was seit dem letzten Mal dazukam.
+/// Die letzte Zeile ohne `\n` (z. B. ein laufender tqdm-Balken) steht auch schon in `lines`.
+pub struct LogReader {
+    pub path: PathBuf,
+    pub lines: Vec<String>,
+    offset: u64,
+    partial: Vec<u8>,
+}
+
+impl LogReader {
+    pub fn new(path: PathBuf) -> Self {
+        LogReader {
+            path,
+            lines: vec![],
+            offset: 0,
+            partial: vec![],
+        }
+    }
+
+    pub fn update(&mut self) {
+        let Ok(mut file) = File::open(&self.path) else {
+            return;
+        };
+
+        let len = file.metadata().map(|m| m.len()).unwrap_or(0);
+        if len == self.offset {
+            return;
+        }
+        if len < self.offset {
+            // Datei wurde neu geschrieben
+            *self = LogReader::new(self.path.clone());
+        }
+
+        let mut new_bytes = vec![];
+        if file.seek(SeekFrom::Start(self.offset)).is_err()
+            || file.read_to_end(&mut new_bytes).is_err()
+        {
+            return;
+        }
+        self.offset += new_bytes.len() as u64;
+
+        // Die unfertige Zeile vom letzten Mal wird gleich neu eingefügt
+        if !self.partial.is_empty() {
+            self.lines.pop();
+        }
+
+        self.partial.extend_from_slice(&new_bytes);
+        let mut start = 0;
+        for (i, byte) in self.partial.iter().enumerate() {
+            if *byte == b'\n' {
+                self.lines.push(clean_line(&self.partial[start..i]));
+                start = i + 1;
+            }
+        }
+        self.partial.drain(..start);
+
+        if !self.partial.is_empty() {
+            self.lines.push(clean_line(&self.partial));
+        }
+    }
+}
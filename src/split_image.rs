@@ -2,6 +2,82 @@ use crate::prelude::*;
 
 use image::{ImageBuffer, RgbaImage};
 
+/// Einstellungen für die Vorhersage in überlappenden Kacheln (siehe `get_tiled_prediction`).
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct TileSettings {
+    pub tile_size: u32,      // Kantenlänge in Pixeln, am besten die imgsz vom Training
+    pub overlap: f32,        // Anteil der Kachel, der mit der nächsten überlappt (0.0..0.9)
+    pub merge_iou: f32,      // ab dieser IoU gelten Boxen aus zwei Kacheln als gleich
+    pub min_confidence: f32, // schwächere Boxen fliegen vor dem Zusammenfassen raus
+}
+
+impl Default for TileSettings {
+    fn default() -> Self {
+        TileSettings {
+            tile_size: 640,
+            overlap: 0.2,
+            merge_iou: 0.3,
+            min_confidence: 0.1,
+        }
+    }
+}
+
+/// Ein Ausschnitt vom Screenshot, `x`/`y` ist die obere linke Ecke im ganzen Bild.
+pub struct Tile {
+    pub x: u32,
+    pub y: u32,
+    pub image: RgbaImage,
+}
+
+impl Tile {
+    /// (x1, y1, x2, y2) im ganzen Bild, wie `Building::bounding_box`.
+    pub fn rect(&self) -> (f32, f32, f32, f32) {
+        (
+            self.x as f32,
+            self.y as f32,
+            (self.x + self.image.width()) as f32,
+            (self.y + self.image.height()) as f32,
+        )
+    }
+}
+
+// Startpunkte entlang einer Achse, die letzte Kachel schließt bündig mit dem Rand ab
+fn tile_origins(len: u32, tile_size: u32, overlap: f32) -> Vec<u32> {
+    if len <= tile_size {
+        return vec![0];
+    }
+
+    let step = ((tile_size as f32 * (1.0 - overlap.clamp(0.0, 0.9))) as u32).max(1);
+    let last = len - tile_size;
+
+    let mut origins: Vec<u32> = (0..last).step_by(step as usize).collect();
+    origins.push(last);
+    origins
+}
+
+/// Schneidet das Bild in überlappende Kacheln. Kleinere Bilder bleiben eine Kachel.
+pub fn tiles(image: &RgbaImage, settings: &TileSettings) -> Vec<Tile> {
+    let tile_size = settings.tile_size.max(32);
+    let xs = tile_origins(image.width(), tile_size, settings.overlap);
+    let ys = tile_origins(image.height(), tile_size, settings.overlap);
+
+    let mut result = vec![];
+    for &y in ys.iter() {
+        for &x in xs.iter() {
+            let width = tile_size.min(image.width() - x);
+            let height = tile_size.min(image.height() - y);
+
+            result.push(Tile {
+                x,
+                y,
+                image: image::imageops::crop_imm(image, x, y, width, height).to_image(),
+            });
+        }
+    }
+
+    result
+}
+
 pub fn split(image_path: &str, num_of_parts: i32, save_path: &str) {
     let image: RgbaImage = image::open(image_path).unwrap().into();
 
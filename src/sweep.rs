@@ -0,0 +1,354 @@
+use crate::prelude::*;
+
+use crate::image_data_wrapper::{EpochMetrics, RatingFormula, TrainingConfig};
+use crate::training_queue::{JobState, TrainingQueue};
+
+// Ein JSON pro Sweep, damit man die Ergebnisse auch nach einem Neustart noch vergleichen kann
+pub const SWEEP_DIR: &str = "sweeps";
+
+/// Trainings-Parameter, über die ein Sweep laufen kann.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, EnumIter)]
+pub enum SweepParam {
+    Imgsz,
+    Batch,
+    Lr0,
+    Lrf,
+    Momentum,
+    WeightDecay,
+    Mosaic,
+    Mixup,
+    Scale,
+    Degrees,
+    Fliplr,
+}
+
+impl SweepParam {
+    pub fn get(&self, config: &TrainingConfig) -> f64 {
+        match self {
+            SweepParam::Imgsz => config.imgsz as f64,
+            SweepParam::Batch => config.batch as f64,
+            SweepParam::Lr0 => config.lr0 as f64,
+            SweepParam::Lrf => config.lrf as f64,
+            SweepParam::Momentum => config.momentum as f64,
+            SweepParam::WeightDecay => config.weight_decay as f64,
+            SweepParam::Mosaic => config.mosaic as f64,
+            SweepParam::Mixup => config.mixup as f64,
+            SweepParam::Scale => config.scale as f64,
+            SweepParam::Degrees => config.degrees as f64,
+            SweepParam::Fliplr => config.fliplr as f64,
+        }
+    }
+
+    pub fn apply(&self, config: &mut TrainingConfig, value: f64) {
+        match self {
+            // ultralytics will ein Vielfaches von 32
+            SweepParam::Imgsz => config.imgsz = ((value / 32.0).round() as u32).max(1) * 32,
+            SweepParam::Batch => config.batch = value.round().max(1.0) as u32,
+            SweepParam::Lr0 => config.lr0 = value as f32,
+            SweepParam::Lrf => config.lrf = value as f32,
+            SweepParam::Momentum => config.momentum = value as f32,
+            SweepParam::WeightDecay => config.weight_decay = value as f32,
+            SweepParam::Mosaic => config.mosaic = value as f32,
+            SweepParam::Mixup => config.mixup = value as f32,
+            SweepParam::Scale => config.scale = value as f32,
+            SweepParam::Degrees => config.degrees = value as f32,
+            SweepParam::Fliplr => config.fliplr = value as f32,
+        }
+    }
+}
+
+impl ToString for SweepParam {
+    fn to_string(&self) -> String {
+        match self {
+            SweepParam::Imgsz => "imgsz".to_string(),
+            SweepParam::Batch => "batch".to_string(),
+            SweepParam::Lr0 => "lr0".to_string(),
+            SweepParam::Lrf => "lrf".to_string(),
+            SweepParam::Momentum => "momentum".to_string(),
+            SweepParam::WeightDecay => "weight_decay".to_string(),
+            SweepParam::Mosaic => "mosaic".to_string(),
+            SweepParam::Mixup => "mixup".to_string(),
+            SweepParam::Scale => "scale".to_string(),
+            SweepParam::Degrees => "degrees".to_string(),
+            SweepParam::Fliplr => "fliplr".to_string(),
+        }
+    }
+}
+
+/// Liest eine Werteliste wie `0.01, 0.005 0.001`.
+pub fn parse_values(text: &str) -> Result<Vec<f64>, FofError> {
+    text.split(|c: char| c == ',' || c == ';' || c.is_whitespace())
+        .filter(|v| !v.is_empty())
+        .map(|v| {
+            v.parse::<f64>()
+                .map_err(|_| FofError::Failed(format!("'{}' ist keine Zahl", v)))
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum SweepMode {
+    Grid,
+    Random(usize), // Anzahl Trials
+}
+
+/// Der Suchraum: eine Basis-Config und pro Parameter die Werte, die probiert werden.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SweepSpace {
+    pub base: TrainingConfig,
+    pub params: Vec<(SweepParam, Vec<f64>)>,
+    pub mode: SweepMode,
+}
+
+impl SweepSpace {
+    fn grid(&self) -> Vec<Vec<(SweepParam, f64)>> {
+        let mut combinations = vec![vec![]];
+
+        for (param, values) in self.params.iter() {
+            combinations = combinations
+                .into_iter()
+                .flat_map(|combination: Vec<(SweepParam, f64)>| {
+                    values.iter().map(move |value| {
+                        let mut next = combination.clone();
+                        next.push((*param, *value));
+                        next
+                    })
+                })
+                .collect();
+        }
+
+        combinations
+    }
+
+    // Sättigt statt überzulaufen, bei vielen Parametern wird das Grid schnell riesig
+    fn grid_size(&self) -> usize {
+        self.params.iter().fold(1usize, |size, (_, values)| {
+            size.saturating_mul(values.len())
+        })
+    }
+
+    /// Alle Kombinationen, die trainiert werden. Bei `Random` höchstens so viele wie das Grid hat.
+    pub fn combinations(&self) -> Vec<Vec<(SweepParam, f64)>> {
+        match self.mode {
+            SweepMode::Grid => self.grid(),
+            // Mehr Trials als Kombinationen, dann einfach alle
+            SweepMode::Random(trials) if trials >= self.grid_size() => self.grid(),
+            SweepMode::Random(trials) => self.sample(trials),
+        }
+    }
+
+    // Zieht pro Parameter einen Wert, ohne das ganze Grid aufzubauen. Doppelte Kombinationen
+    // werden neu gezogen, das endet schnell, weil es weniger Trials als Kombinationen gibt.
+    fn sample(&self, trials: usize) -> Vec<Vec<(SweepParam, f64)>> {
+        let mut rng = rand::rng();
+        let mut seen: HashSet<Vec<usize>> = HashSet::new();
+        let mut picked = vec![];
+
+        while picked.len() < trials {
+            let indices: Vec<usize> = self
+                .params
+                .iter()
+                .map(|(_, values)| rng.random_range(0..values.len()))
+                .collect();
+
+            if !seen.insert(indices.clone()) {
+                continue;
+            }
+
+            picked.push(
+                self.params
+                    .iter()
+                    .zip(indices)
+                    .map(|((param, values), i)| (*param, values[i]))
+                    .collect(),
+            );
+        }
+
+        picked
+    }
+
+    pub fn trial_count(&self) -> usize {
+        match self.mode {
+            SweepMode::Grid => self.grid_size(),
+            SweepMode::Random(trials) => trials.min(self.grid_size()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SweepTrial {
+    pub model_name: String,
+    pub values: Vec<(SweepParam, f64)>,
+    pub config: TrainingConfig,
+}
+
+/// Ergebnis von einem Trial, so wie es gerade auf der Platte steht.
+#[derive(Debug, Clone)]
+pub struct TrialResult {
+    pub state: Option<JobState>, // None = nicht (mehr) in der Warteschlange
+    pub epochs: usize,
+    pub best: Option<EpochMetrics>, // die Epoche von `best.pt`, die auch beim Übernehmen geforkt wird
+    pub score: Option<f64>,
+}
+
+/// Jeder Trial ist ein Fork vom Parent-Modell, der mit seiner Config über die
+/// Trainings-Warteschlange trainiert wird.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Sweep {
+    pub name: String,
+    pub parent_model: String,
+    pub epochen: usize,
+    pub created_at: String,
+    pub trials: Vec<SweepTrial>,
+}
+
+impl Sweep {
+    pub fn path(name: &str) -> PathBuf {
+        Path::new(SWEEP_DIR).join(format!("{}.json", name))
+    }
+
+    /// Legt alle Trial-Modelle an und reiht sie in die Warteschlange ein.
+    pub fn start(
+        name: &str,
+        parent_model: &str,
+        epochen: usize,
+        space: &SweepSpace,
+        queue: &mut TrainingQueue,
+    ) -> Result<Sweep, FofError> {
+        if Sweep::path(name).exists() {
+            return Err(FofError::Failed(format!("Sweep {} gibt es schon", name)));
+        }
+
+        let combinations = space.combinations();
+        if combinations.is_empty() {
+            return Err(FofError::Failed("Keine Werte zum Ausprobieren".to_string()));
+        }
+
+        let mut trials = vec![];
+        for (i, values) in combinations.into_iter().enumerate() {
+            let mut config = space.base.clone();
+            for (param, value) in values.iter() {
+                param.apply(&mut config, *value);
+            }
+
+            trials.push(SweepTrial {
+                model_name: format!("{}_t{:02}", name, i + 1),
+                values,
+                config,
+            });
+        }
+
+        // Erst alle Forks, damit bei einem Fehler nichts halb in der Warteschlange landet
+        for (i, trial) in trials.iter().enumerate() {
+            if let Err(e) = image_data_wrapper::fork_model(parent_model, &trial.model_name) {
+                for created in trials[..i].iter() {
+                    let _ = image_data_wrapper::delete_model(&created.model_name);
+                }
+                return Err(e);
+            }
+        }
+
+        let sweep = Sweep {
+            name: name.to_string(),
+            parent_model: parent_model.to_string(),
+            epochen,
+            created_at: Local::now().to_rfc3339(),
+            trials,
+        };
+        sweep.save()?;
+
+        for trial in sweep.trials.iter() {
+            queue.enqueue(&trial.model_name, epochen, trial.config.clone())?;
+        }
+
+        Ok(sweep)
+    }
+
+    pub fn save(&self) -> Result<(), FofError> {
+        fs::create_dir_all(SWEEP_DIR)?;
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| FofError::JsonParseError(e.to_string()))?;
+        fs::write(Sweep::path(&self.name), json)?;
+        Ok(())
+    }
+
+    /// Alle gespeicherten Sweeps, der neueste zuerst. Kaputte Dateien werden übersprungen.
+    pub fn load_all() -> Vec<Sweep> {
+        let Ok(entries) = fs::read_dir(SWEEP_DIR) else {
+            return vec![];
+        };
+
+        let mut sweeps: Vec<Sweep> = entries
+            .flatten()
+            .filter(|e| e.path().extension().map(|x| x == "json").unwrap_or(false))
+            .filter_map(|e| fs::read_to_string(e.path()).ok())
+            .filter_map(|s| serde_json::from_str(&s).ok())
+            .collect();
+
+        sweeps.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+        sweeps
+    }
+
+    /// Löscht den Sweep und, wenn gewünscht, alle Trial-Modelle die nicht mehr trainieren.
+    pub fn delete(&self, with_models: bool, queue: &mut TrainingQueue) -> Result<(), FofError> {
+        if with_models {
+            for trial in self.trials.iter() {
+                if queue.is_training(&trial.model_name) {
+                    continue;
+                }
+
+                let queued: Vec<String> = queue
+                    .jobs
+                    .iter()
+                    .filter(|j| j.model_name == trial.model_name)
+                    .map(|j| j.id.clone())
+                    .collect();
+                for id in queued {
+                    queue.remove(&id)?;
+                }
+
+                if Path::new(&format!("runs/detect/{}", trial.model_name)).exists() {
+                    image_data_wrapper::delete_model(&trial.model_name)?;
+                }
+            }
+        }
+
+        fs::remove_file(Sweep::path(&self.name))?;
+        Ok(())
+    }
+
+    pub fn results(&self, queue: &TrainingQueue, formula: &RatingFormula) -> Vec<TrialResult> {
+        self.trials
+            .iter()
+            .map(|trial| {
+                let state = queue
+                    .jobs
+                    .iter()
+                    .rev()
+                    .find(|j| j.model_name == trial.model_name)
+                    .map(|j| j.state.clone());
+
+                let history =
+                    image_data_wrapper::get_metrics_history(&trial.model_name).unwrap_or_default();
+                let best = image_data_wrapper::best_pt_epoch(&history).cloned();
+
+                TrialResult {
+                    state,
+                    epochs: history.len(),
+                    score: best.as_ref().map(|m| formula.score(m)),
+                    best,
+                }
+            })
+            .collect()
+    }
+
+    /// Index vom Trial mit dem höchsten Score.
+    pub fn best_trial(results: &[TrialResult]) -> Option<usize> {
+        results
+            .iter()
+            .enumerate()
+            .filter_map(|(i, r)| r.score.map(|s| (i, s)))
+            .max_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal))
+            .map(|(i, _)| i)
+    }
+}
@@ -0,0 +1,164 @@
+use crate::image_data_wrapper::{read_metrics_csv, EpochMetrics};
+use crate::prelude::*;
+use crate::run_log::RunKind;
+
+use std::collections::VecDeque;
+use std::io::{Seek, SeekFrom};
+use std::sync::OnceLock;
+use std::time::{Duration, SystemTime};
+
+// So viele Zeilen vom Python Output werden in der UI behalten
+pub const MAX_LOG_LINES: usize = 200;
+
+/// Hier landet stdout + stderr vom Trainingsprozess. Eine Datei statt einer Pipe,
+/// damit das Training weiterläuft, wenn die UI geschlossen wird.
+/// Das neueste `*_train.log` aus `run_log`, sonst das alte `train.log`.
+/// Sucht im Log-Ordner, also nicht in jedem Frame aufrufen.
+pub fn train_log_path(model_name: &str) -> String {
+    match run_log::latest_log(model_name, RunKind::Train) {
+        Some(path) => path.to_string_lossy().to_string(),
+        None => format!("runs/detect/{}/train.log", model_name),
+    }
+}
+
+/// ANSI-Farben im Python Output. Wird nur einmal kompiliert, `push_line` läuft für jede Zeile.
+pub fn ansi_regex() -> &'static regex::Regex {
+    static ANSI: OnceLock<regex::Regex> = OnceLock::new();
+    ANSI.get_or_init(|| regex::Regex::new(r"\x1b\[[0-9;]*[A-Za-z]").unwrap())
+}
+
+/// Fortschritt von einem laufenden Training: liest `results.csv` und den Output vom Prozess mit.
+pub struct TrainingProgress {
+    pub model_name: String,
+    pub total_epochs: usize,
+    pub epochs: Vec<EpochMetrics>,
+    pub log_lines: VecDeque<String>,
+    // Einmal beim Anlegen bestimmt, das Log vom Training ändert sich danach nicht mehr
+    log_path: String,
+    started_at: SystemTime,
+    results_modified: Option<SystemTime>,
+    log_offset: u64,
+    partial_line: Vec<u8>,
+    last_overwrite: bool,
+}
+
+impl TrainingProgress {
+    pub fn new(model_name: &str, total_epochs: usize) -> Self {
+        TrainingProgress::since(model_name, total_epochs, SystemTime::now())
+    }
+
+    /// Für ein Training, das schon vor `started_at` gestartet wurde (z. B. nach einem Neustart der UI).
+    pub fn since(model_name: &str, total_epochs: usize, started_at: SystemTime) -> Self {
+        TrainingProgress {
+            model_name: model_name.to_string(),
+            total_epochs,
+            epochs: vec![],
+            log_lines: VecDeque::new(),
+            log_path: train_log_path(model_name),
+            started_at,
+            results_modified: None,
+            log_offset: 0,
+            partial_line: vec![],
+            last_overwrite: false,
+        }
+    }
+
+    fn results_path(&self) -> String {
+        format!("runs/detect/{}/results.csv", self.model_name)
+    }
+
+    fn push_line(&mut self, bytes: &[u8], overwrite: bool) {
+        let text = String::from_utf8_lossy(bytes).to_string();
+        let text = ansi_regex().replace_all(&text, "").trim_end().to_string();
+
+        if text.is_empty() {
+            return;
+        }
+
+        // tqdm schreibt Fortschrittsbalken mit '\r' immer wieder in die gleiche Zeile
+        if self.last_overwrite && overwrite {
+            self.log_lines.pop_back();
+        }
+        self.last_overwrite = overwrite;
+
+        self.log_lines.push_back(text);
+        if self.log_lines.len() > MAX_LOG_LINES {
+            self.log_lines.pop_front();
+        }
+    }
+
+    fn read_new_output(&mut self) {
+        let Ok(mut file) = File::open(&self.log_path) else {
+            return;
+        };
+
+        let len = file.metadata().map(|m| m.len()).unwrap_or(0);
+        if len < self.log_offset {
+            // Datei wurde neu angelegt (neues Training)
+            self.log_offset = 0;
+            self.partial_line.clear();
+        }
+
+        let mut new_bytes = vec![];
+        if file.seek(SeekFrom::Start(self.log_offset)).is_err()
+            || file.read_to_end(&mut new_bytes).is_err()
+        {
+            return;
+        }
+        self.log_offset += new_bytes.len() as u64;
+
+        for byte in new_bytes {
+            if byte != b'\n' && byte != b'\r' {
+                self.partial_line.push(byte);
+                continue;
+            }
+
+            let line = std::mem::take(&mut self.partial_line);
+            self.push_line(&line, byte == b'\r');
+        }
+    }
+
+    pub fn update(&mut self) {
+        self.read_new_output();
+
+        let Ok(modified) = fs::metadata(self.results_path()).and_then(|m| m.modified()) else {
+            return;
+        };
+
+        // ultralytics überschreibt results.csv beim ersten Epoch, bis dahin steht da noch das alte Training
+        if modified < self.started_at || Some(modified) == self.results_modified {
+            return;
+        }
+
+        if let Ok(epochs) = read_metrics_csv(self.results_path()) {
+            self.epochs = epochs;
+            self.results_modified = Some(modified);
+        }
+    }
+
+    pub fn current_epoch(&self) -> usize {
+        self.epochs.last().map(|e| e.epoch).unwrap_or(0)
+    }
+
+    pub fn latest(&self) -> Option<&EpochMetrics> {
+        self.epochs.last()
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        SystemTime::now()
+            .duration_since(self.started_at)
+            .unwrap_or_default()
+    }
+
+    /// Geschätzte Restzeit aus der bisherigen Zeit pro Epoche.
+    pub fn eta(&self) -> Option<Duration> {
+        let done = self.epochs.len();
+        if done == 0 {
+            return None;
+        }
+
+        let per_epoch = self.elapsed() / done as u32;
+        let remaining = self.total_epochs.saturating_sub(done) as u32;
+        Some(per_epoch * remaining)
+    }
+}
@@ -0,0 +1,728 @@
+use crate::prelude::*;
+
+use crate::image_data_wrapper::TrainingConfig;
+use crate::training_progress::TrainingProgress;
+use std::time::{Duration, Instant, SystemTime};
+
+// Liegt neben runs/, damit die UI nach einem Neustart weiß, was noch läuft
+pub const QUEUE_FILE: &str = "training_queue.json";
+
+// So lange darf Python nach SIGINT noch aufräumen, danach wird hart beendet
+pub const INTERRUPT_TIMEOUT: Duration = Duration::from_secs(30);
+
+// `tick` läuft jeden Frame, Prozess und Dateien werden aber nur so oft angeschaut
+pub const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum JobState {
+    Queued,
+    Running,
+    Finished,
+    Stopped(usize), // auf Wunsch nach dieser Epoche beendet, kann fortgesetzt werden
+    Failed(String), // Grund
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrainingJob {
+    pub id: String,
+    pub model_name: String,
+    pub epochen: usize,
+    #[serde(default)]
+    pub config: TrainingConfig,
+    pub state: JobState,
+    pub pid: Option<u32>,
+    // Startzeit vom Prozess laut Betriebssystem. PIDs werden wiederverwendet,
+    // erst PID + Startzeit sagen sicher, dass es noch unser Python ist.
+    #[serde(default)]
+    pub pid_started: Option<String>,
+    pub queued_at: String,
+    pub started_at: Option<String>,
+    pub finished_at: Option<String>,
+    #[serde(default)]
+    pub resume_from: Option<usize>, // Some = setzt ein abgebrochenes Training ab dieser Epoche fort
+    #[serde(default)]
+    pub stop_requested: bool,
+    #[serde(default)]
+    pub interrupted_at: Option<String>, // wann SIGINT geschickt wurde
+}
+
+fn parse_time(t: &Option<String>) -> Option<SystemTime> {
+    t.as_ref()
+        .and_then(|t| chrono::DateTime::parse_from_rfc3339(t).ok())
+        .map(SystemTime::from)
+}
+
+impl TrainingJob {
+    fn started_time(&self) -> SystemTime {
+        parse_time(&self.started_at).unwrap_or_else(SystemTime::now)
+    }
+
+    /// Läuft der Prozess von diesem Job noch?
+    fn process_alive(&self) -> bool {
+        match (self.pid, &self.pid_started) {
+            (Some(pid), Some(started)) => process_start(pid).as_ref() == Some(started),
+            // Einträge von vor der Startzeit, da bleibt nur die PID
+            (Some(pid), None) => pid_alive(pid),
+            (None, _) => false,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum QueueEvent {
+    Started(String),
+    Finished(String),
+    Stopped(String),
+    Failed(String, String), // Modell, Grund
+    Reattached(String),
+}
+
+fn now() -> String {
+    Local::now().to_rfc3339()
+}
+
+#[cfg(unix)]
+fn pid_alive(pid: u32) -> bool {
+    Command::new("kill")
+        .arg("-0")
+        .arg(pid.to_string())
+        .stderr(std::process::Stdio::null())
+        .status()
+        .map(|s| s.success())
+        .unwrap_or(false)
+}
+
+#[cfg(windows)]
+fn pid_alive(pid: u32) -> bool {
+    Command::new("tasklist")
+        .args(["/FI", &format!("PID eq {}", pid), "/NH"])
+        .output()
+        .map(|o| String::from_utf8_lossy(&o.stdout).contains(&pid.to_string()))
+        .unwrap_or(false)
+}
+
+/// Startzeit vom Prozess als Text, `None` wenn es ihn nicht gibt.
+#[cfg(unix)]
+fn process_start(pid: u32) -> Option<String> {
+    let output = Command::new("ps")
+        .args(["-o", "lstart=", "-p", &pid.to_string()])
+        .stderr(std::process::Stdio::null())
+        .output()
+        .ok()?;
+    let started = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (output.status.success() && !started.is_empty()).then_some(started)
+}
+
+#[cfg(windows)]
+fn process_start(pid: u32) -> Option<String> {
+    let output = Command::new("powershell")
+        .args([
+            "-NoProfile",
+            "-Command",
+            &format!(
+                "(Get-Process -Id {} -ErrorAction SilentlyContinue).StartTime.ToFileTimeUtc()",
+                pid
+            ),
+        ])
+        .output()
+        .ok()?;
+    let started = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (output.status.success() && !started.is_empty()).then_some(started)
+}
+
+#[cfg(unix)]
+fn kill_pid(pid: u32) -> Result<(), FofError> {
+    let status = Command::new("kill")
+        .arg(pid.to_string())
+        .status()
+        .map_err(|_| FofError::FailedToStopTraining)?;
+    if !status.success() {
+        return Err(FofError::FailedToStopTraining);
+    }
+    Ok(())
+}
+
+#[cfg(windows)]
+fn kill_pid(pid: u32) -> Result<(), FofError> {
+    let status = Command::new("taskkill")
+        .args(["/PID", &pid.to_string(), "/F"])
+        .status()
+        .map_err(|_| FofError::FailedToStopTraining)?;
+    if !status.success() {
+        return Err(FofError::FailedToStopTraining);
+    }
+    Ok(())
+}
+
+#[cfg(unix)]
+fn interrupt_pid(pid: u32) -> Result<(), FofError> {
+    let status = Command::new("kill")
+        .args(["-INT", &pid.to_string()])
+        .status()
+        .map_err(|_| FofError::FailedToStopTraining)?;
+    if !status.success() {
+        return Err(FofError::FailedToStopTraining);
+    }
+    Ok(())
+}
+
+// Windows kennt kein SIGINT für fremde Prozesse, taskkill ohne /F ist das Nächste.
+// Ein Konsolen-python ohne Fenster ignoriert das aber, dann bleibt nur hart beenden.
+#[cfg(windows)]
+fn interrupt_pid(pid: u32) -> Result<(), FofError> {
+    let soft = Command::new("taskkill")
+        .args(["/PID", &pid.to_string()])
+        .status()
+        .map(|s| s.success())
+        .unwrap_or(false);
+    if soft {
+        return Ok(());
+    }
+    kill_pid(pid)
+}
+
+/// Modelle, die laut `QUEUE_FILE` gerade trainiert werden. Ändert die Datei nicht.
+pub fn running_models() -> Vec<String> {
+    let jobs: Vec<TrainingJob> = fs::read_to_string(QUEUE_FILE)
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default();
+
+    jobs.into_iter()
+        .filter(|j| j.state == JobState::Running && j.process_alive())
+        .map(|j| j.model_name)
+        .collect()
+}
+
+/// Trainings-Warteschlange. Es läuft immer nur ein Training gleichzeitig (eine GPU),
+/// der Zustand wird bei jeder Änderung in `QUEUE_FILE` gespeichert.
+pub struct TrainingQueue {
+    path: PathBuf,
+    pub jobs: Vec<TrainingJob>,
+    // Nur gesetzt, wenn wir den Prozess selbst gestartet haben. Nach einem Neustart kennen wir nur PID + Startzeit.
+    child: Option<Child>,
+    pub progress: Option<TrainingProgress>,
+    events: Vec<QueueEvent>,
+    // Jobs wurden in `tick` geändert und müssen noch gespeichert werden
+    dirty: bool,
+    last_poll: Option<Instant>,
+}
+
+impl TrainingQueue {
+    pub fn new<P: AsRef<Path>>(path: P) -> Self {
+        TrainingQueue {
+            path: path.as_ref().to_path_buf(),
+            jobs: vec![],
+            child: None,
+            progress: None,
+            events: vec![],
+            dirty: false,
+            last_poll: None,
+        }
+    }
+
+    /// Lädt die Warteschlange und schaut, ob als "Running" gespeicherte Jobs noch leben.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, FofError> {
+        let path = path.as_ref().to_path_buf();
+
+        let jobs: Vec<TrainingJob> = if path.exists() {
+            let content = fs::read_to_string(&path)
+                .map_err(|_| FofError::FailedReadingFile(path.display().to_string()))?;
+            serde_json::from_str(&content).map_err(|e| FofError::JsonParseError(e.to_string()))?
+        } else {
+            vec![]
+        };
+
+        let mut queue = TrainingQueue::new(path);
+        queue.jobs = jobs;
+
+        if let Some(job) = queue.running_job().cloned() {
+            if job.process_alive() {
+                queue.progress = Some(TrainingProgress::since(
+                    &job.model_name,
+                    job.epochen,
+                    job.started_time(),
+                ));
+                queue.events.push(QueueEvent::Reattached(job.model_name));
+            } else {
+                queue.finish_without_child(&job.id);
+            }
+        }
+
+        queue.save()?;
+        Ok(queue)
+    }
+
+    pub fn save(&self) -> Result<(), FofError> {
+        let json = serde_json::to_string_pretty(&self.jobs)
+            .map_err(|e| FofError::JsonParseError(e.to_string()))?;
+        fs::write(&self.path, json)?;
+        Ok(())
+    }
+
+    pub fn enqueue(
+        &mut self,
+        model_name: &str,
+        epochen: usize,
+        config: TrainingConfig,
+    ) -> Result<(), FofError> {
+        self.jobs.push(TrainingJob {
+            id: uuid::Uuid::new_v4().to_string(),
+            model_name: model_name.to_string(),
+            epochen,
+            config,
+            state: JobState::Queued,
+            pid: None,
+            pid_started: None,
+            queued_at: now(),
+            started_at: None,
+            finished_at: None,
+            resume_from: None,
+            stop_requested: false,
+            interrupted_at: None,
+        });
+        self.save()
+    }
+
+    /// Reiht das Fortsetzen vom zuletzt abgebrochenen Training ein (siehe `ModelMetadata::interrupted`).
+    pub fn enqueue_resume(&mut self, model_name: &str) -> Result<(), FofError> {
+        let interrupted = image_data_wrapper::ModelMetadata::load(model_name)?
+            .interrupted
+            .ok_or_else(|| {
+                FofError::Failed(format!("{} hat kein abgebrochenes Training", model_name))
+            })?;
+
+        self.jobs.push(TrainingJob {
+            id: uuid::Uuid::new_v4().to_string(),
+            model_name: model_name.to_string(),
+            epochen: interrupted.epochen,
+            config: interrupted.config,
+            state: JobState::Queued,
+            pid: None,
+            pid_started: None,
+            queued_at: now(),
+            started_at: None,
+            finished_at: None,
+            resume_from: Some(interrupted.epochs_done),
+            stop_requested: false,
+            interrupted_at: None,
+        });
+        self.save()
+    }
+
+    /// Entfernt einen wartenden oder abgeschlossenen Job. Laufende Jobs muss man erst stoppen.
+    pub fn remove(&mut self, id: &str) -> Result<(), FofError> {
+        self.jobs
+            .retain(|j| j.id != id || j.state == JobState::Running);
+        self.save()
+    }
+
+    /// Alle fertigen, gestoppten und fehlgeschlagenen Jobs aus der Liste löschen.
+    pub fn clear_done(&mut self) -> Result<(), FofError> {
+        self.jobs
+            .retain(|j| matches!(j.state, JobState::Queued | JobState::Running));
+        self.save()
+    }
+
+    fn job_mut(&mut self, id: &str) -> Option<&mut TrainingJob> {
+        self.dirty = true;
+        self.jobs.iter_mut().find(|j| j.id == id)
+    }
+
+    pub fn running_job(&self) -> Option<&TrainingJob> {
+        self.jobs.iter().find(|j| j.state == JobState::Running)
+    }
+
+    pub fn is_training(&self, model_name: &str) -> bool {
+        self.running_job()
+            .is_some_and(|j| j.model_name == model_name)
+    }
+
+    pub fn is_queued(&self, model_name: &str) -> bool {
+        self.jobs
+            .iter()
+            .any(|j| j.model_name == model_name && j.state == JobState::Queued)
+    }
+
+    fn set_state(&mut self, id: &str, state: JobState) {
+        if let Some(job) = self.job_mut(id) {
+            let event = match &state {
+                JobState::Finished => Some(QueueEvent::Finished(job.model_name.clone())),
+                JobState::Stopped(_) => Some(QueueEvent::Stopped(job.model_name.clone())),
+                JobState::Failed(reason) => {
+                    Some(QueueEvent::Failed(job.model_name.clone(), reason.clone()))
+                }
+                _ => None,
+            };
+
+            let done = matches!(
+                state,
+                JobState::Finished | JobState::Stopped(_) | JobState::Failed(_)
+            );
+            if done {
+                job.finished_at = Some(now());
+            }
+            job.state = state;
+
+            // Nur Jobs mit eigenem Prozess, die anderen zählen ihre Epochen selbst (siehe start_next)
+            if done && job.pid.is_some() {
+                let mut progress =
+                    TrainingProgress::since(&job.model_name, job.epochen, job.started_time());
+                progress.update();
+
+                // Beim Fortsetzen hängt ultralytics an die alte results.csv an
+                let trained = match job.resume_from {
+                    Some(from) => progress.current_epoch().saturating_sub(from),
+                    None => progress.epochs.len(),
+                };
+
+                if let Err(e) =
+                    image_data_wrapper::record_trained_epochs(&job.model_name, trained as u32)
+                {
+                    eprintln!("Konnte Epochen nicht in model.json speichern: {:?}", e);
+                }
+
+                let interrupted = match &job.state {
+                    JobState::Stopped(_) | JobState::Failed(_)
+                        if image_data_wrapper::WeightsStatus::of(&job.model_name).can_resume() =>
+                    {
+                        Some(image_data_wrapper::InterruptedTraining {
+                            epochen: job.epochen,
+                            epochs_done: progress.current_epoch(),
+                            config: job.config.clone(),
+                        })
+                    }
+                    _ => None,
+                };
+
+                if let Err(e) = image_data_wrapper::ModelMetadata::update(&job.model_name, |m| {
+                    m.interrupted = interrupted
+                }) {
+                    eprintln!("Konnte Abbruch nicht in model.json speichern: {:?}", e);
+                }
+            }
+
+            if let Some(event) = event {
+                self.events.push(event);
+            }
+        }
+    }
+
+    // Ohne Child wissen wir den Exit-Code nicht, also schauen wir, ob alle Epochen in results.csv stehen
+    fn finish_without_child(&mut self, id: &str) {
+        let Some(job) = self.jobs.iter().find(|j| j.id == id).cloned() else {
+            return;
+        };
+
+        let mut progress =
+            TrainingProgress::since(&job.model_name, job.epochen, job.started_time());
+        progress.update();
+
+        // ultralytics schreibt am Ende "N epochs completed in ...", auch wenn es wegen patience früher aufhört
+        let completed = fs::read_to_string(training_progress::train_log_path(&job.model_name))
+            .map(|log| log.contains("epochs completed"))
+            .unwrap_or(false);
+
+        if job.stop_requested {
+            self.set_state(id, JobState::Stopped(progress.current_epoch()));
+        } else if completed || progress.current_epoch() >= job.epochen {
+            self.set_state(id, JobState::Finished);
+        } else {
+            self.set_state(
+                id,
+                JobState::Failed(format!(
+                    "Prozess beendet nach Epoche {}/{}",
+                    progress.current_epoch(),
+                    job.epochen
+                )),
+            );
+        }
+    }
+
+    fn start_next(&mut self) {
+        let Some(job) = self
+            .jobs
+            .iter()
+            .find(|j| j.state == JobState::Queued)
+            .cloned()
+        else {
+            return;
+        };
+
+        match image_data_wrapper::start_training(
+            &job.model_name,
+            job.epochen as i32,
+            &job.config,
+            job.resume_from.is_some(),
+        ) {
+            Ok(Some(child)) => {
+                if let Some(j) = self.job_mut(&job.id) {
+                    j.state = JobState::Running;
+                    j.pid = Some(child.id());
+                    j.pid_started = process_start(child.id());
+                    j.started_at = Some(now());
+                }
+                self.progress = Some(TrainingProgress::new(&job.model_name, job.epochen));
+                self.child = Some(child);
+                self.events.push(QueueEvent::Started(job.model_name));
+            }
+            // Backends ohne eigenen Prozess (Fake Backend) sind sofort fertig
+            Ok(None) => {
+                if let Some(j) = self.job_mut(&job.id) {
+                    j.started_at = Some(now());
+                }
+                if let Err(e) =
+                    image_data_wrapper::record_trained_epochs(&job.model_name, job.epochen as u32)
+                {
+                    eprintln!("Konnte Epochen nicht in model.json speichern: {:?}", e);
+                }
+                self.set_state(&job.id, JobState::Finished);
+            }
+            Err(e) => self.set_state(&job.id, JobState::Failed(format!("{:?}", e))),
+        }
+    }
+
+    /// Muss regelmäßig aufgerufen werden (jeden Frame): prüft höchstens alle `POLL_INTERVAL`
+    /// den laufenden Prozess und startet den nächsten Job. Gibt zurück, was seit dem letzten Aufruf passiert ist.
+    pub fn tick(&mut self) -> Vec<QueueEvent> {
+        let due = self
+            .last_poll
+            .map(|t| t.elapsed() >= POLL_INTERVAL)
+            .unwrap_or(true);
+        if due {
+            self.last_poll = Some(Instant::now());
+            self.poll();
+        }
+
+        if self.dirty {
+            self.dirty = false;
+            if let Err(e) = self.save() {
+                eprintln!("Konnte {} nicht speichern: {:?}", self.path.display(), e);
+            }
+        }
+
+        std::mem::take(&mut self.events)
+    }
+
+    fn poll(&mut self) {
+        if let Some(progress) = self.progress.as_mut() {
+            progress.update();
+        }
+
+        if let Some(job) = self.running_job().cloned() {
+            self.escalate_stop(&job);
+
+            let exited = match self.child.as_mut() {
+                Some(child) => match child.try_wait() {
+                    Ok(Some(_)) if job.stop_requested => {
+                        let epoch = self
+                            .progress
+                            .as_ref()
+                            .map(|p| p.current_epoch())
+                            .unwrap_or(0);
+                        Some(JobState::Stopped(epoch))
+                    }
+                    Ok(Some(status)) if status.success() => Some(JobState::Finished),
+                    Ok(Some(status)) => {
+                        Some(JobState::Failed(format!("Python beendet mit {}", status)))
+                    }
+                    Ok(None) => None,
+                    Err(e) => Some(JobState::Failed(e.to_string())),
+                },
+                None if job.process_alive() => None,
+                None => {
+                    self.finish_without_child(&job.id);
+                    self.progress = None;
+                    None
+                }
+            };
+
+            if let Some(state) = exited {
+                self.set_state(&job.id, state);
+                self.child = None;
+                self.progress = None;
+            }
+        }
+
+        if self.running_job().is_none() {
+            self.start_next();
+        }
+    }
+
+    /// Bittet das laufende Training, nach der aktuellen Epoche aufzuhören.
+    /// Der Job bleibt "Running", bis Python sich beendet hat (siehe `tick`).
+    pub fn request_stop(&mut self) -> Result<(), FofError> {
+        let Some(job) = self.running_job().cloned() else {
+            return Err(FofError::NoTrainingRunning);
+        };
+
+        image_data_wrapper::request_stop(&job.model_name)?;
+
+        if let Some(j) = self.job_mut(&job.id) {
+            j.stop_requested = true;
+        }
+        self.save()
+    }
+
+    /// Schickt SIGINT. Wenn Python nach `INTERRUPT_TIMEOUT` noch läuft, wird hart beendet.
+    pub fn interrupt_running(&mut self) -> Result<(), FofError> {
+        let Some(job) = self.running_job().cloned() else {
+            return Err(FofError::NoTrainingRunning);
+        };
+
+        let pid = job.pid.ok_or(FofError::NoTrainingRunning)?;
+        // Ohne eigenes Child könnte die PID inzwischen zu einem fremden Prozess gehören
+        if self.child.is_none() && !job.process_alive() {
+            return Err(FofError::NoTrainingRunning);
+        }
+        let res = interrupt_pid(pid);
+
+        // Auch wenn das Signal nicht ankam: nach dem Timeout wird hart beendet
+        if let Some(j) = self.job_mut(&job.id) {
+            j.stop_requested = true;
+            j.interrupted_at = Some(now());
+        }
+        self.save()?;
+        res
+    }
+
+    fn escalate_stop(&mut self, job: &TrainingJob) {
+        let Some(interrupted_at) = parse_time(&job.interrupted_at) else {
+            return;
+        };
+
+        let waited = SystemTime::now()
+            .duration_since(interrupted_at)
+            .unwrap_or_default();
+        if waited < INTERRUPT_TIMEOUT {
+            return;
+        }
+
+        let res = match self.child.as_mut() {
+            Some(child) => child.kill().map_err(|_| FofError::FailedToStopTraining),
+            None => match job.pid {
+                Some(pid) if job.process_alive() => kill_pid(pid),
+                _ => Ok(()),
+            },
+        };
+
+        if let Err(e) = res {
+            eprintln!("Konnte Training nicht beenden: {:?}", e);
+        }
+
+        // Nur einmal eskalieren
+        if let Some(j) = self.job_mut(&job.id) {
+            j.interrupted_at = None;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_queue_path() -> PathBuf {
+        std::env::temp_dir().join(format!("fof_queue_{}.json", uuid::Uuid::new_v4()))
+    }
+
+    fn job(model_name: &str, state: JobState) -> TrainingJob {
+        TrainingJob {
+            id: uuid::Uuid::new_v4().to_string(),
+            model_name: model_name.to_string(),
+            epochen: 10,
+            config: TrainingConfig::default(),
+            state,
+            pid: None,
+            pid_started: None,
+            queued_at: now(),
+            started_at: None,
+            finished_at: None,
+            resume_from: None,
+            stop_requested: false,
+            interrupted_at: None,
+        }
+    }
+
+    #[test]
+    fn queue_survives_a_restart() {
+        let path = temp_queue_path();
+        let mut queue = TrainingQueue::new(&path);
+        let config = TrainingConfig {
+            batch: 7,
+            ..Default::default()
+        };
+        queue.enqueue("test_modell", 25, config).unwrap();
+
+        let loaded = TrainingQueue::load(&path).unwrap();
+        let _ = fs::remove_file(&path);
+
+        assert_eq!(loaded.jobs.len(), 1);
+        assert_eq!(loaded.jobs[0].model_name, "test_modell");
+        assert_eq!(loaded.jobs[0].epochen, 25);
+        assert_eq!(loaded.jobs[0].config.batch, 7);
+        assert_eq!(loaded.jobs[0].state, JobState::Queued);
+        assert!(loaded.is_queued("test_modell"));
+    }
+
+    #[test]
+    fn dead_running_jobs_are_finished_on_load() {
+        let path = temp_queue_path();
+        let mut queue = TrainingQueue::new(&path);
+        let mut stopped = job("gestoppt_modell", JobState::Running);
+        stopped.stop_requested = true;
+        queue.jobs = vec![stopped];
+        queue.save().unwrap();
+
+        let loaded = TrainingQueue::load(&path).unwrap();
+        assert_eq!(loaded.jobs[0].state, JobState::Stopped(0));
+        assert!(loaded.jobs[0].finished_at.is_some());
+
+        // Der neue Zustand steht auch in der Datei
+        let reloaded = TrainingQueue::load(&path).unwrap();
+        let _ = fs::remove_file(&path);
+        assert_eq!(reloaded.jobs[0].state, JobState::Stopped(0));
+    }
+
+    #[test]
+    fn crashed_running_job_without_stop_fails() {
+        let path = temp_queue_path();
+        let mut queue = TrainingQueue::new(&path);
+        queue.jobs = vec![job("kaputt_modell", JobState::Running)];
+        queue.save().unwrap();
+
+        let loaded = TrainingQueue::load(&path).unwrap();
+        let _ = fs::remove_file(&path);
+        assert!(matches!(loaded.jobs[0].state, JobState::Failed(_)));
+        assert!(loaded.running_job().is_none());
+    }
+
+    #[test]
+    fn clear_done_keeps_open_jobs() {
+        let path = temp_queue_path();
+        let mut queue = TrainingQueue::new(&path);
+        queue.jobs = vec![
+            job("a", JobState::Queued),
+            job("b", JobState::Finished),
+            job("c", JobState::Stopped(3)),
+            job("d", JobState::Failed("x".to_string())),
+        ];
+        queue.clear_done().unwrap();
+        let _ = fs::remove_file(&path);
+
+        let names: Vec<&str> = queue.jobs.iter().map(|j| j.model_name.as_str()).collect();
+        assert_eq!(names, vec!["a"]);
+    }
+
+    #[test]
+    fn reused_pid_is_not_our_process() {
+        let mut ours = job("pid_modell", JobState::Running);
+        ours.pid = Some(std::process::id());
+        ours.pid_started = process_start(std::process::id());
+        assert!(ours.pid_started.is_some());
+        assert!(ours.process_alive());
+
+        // Gleiche PID, aber zu einer anderen Zeit gestartet
+        ours.pid_started = Some("Thu Jan  1 00:00:00 1970".to_string());
+        assert!(!ours.process_alive());
+    }
+}
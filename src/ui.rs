@@ -17,7 +17,21 @@ const GREEN: egui::Color32 = egui::Color32::from_rgb(0, 200, 100);
 const YELLOW: egui::Color32 = egui::Color32::from_rgb(255, 180, 0);
 const RED: egui::Color32 = egui::Color32::from_rgb(200, 50, 50);
 
+// So oft werden Log-Liste und offenes Log im Log-Viewer neu gelesen
+const LOG_REFRESH: std::time::Duration = std::time::Duration::from_secs(1);
+
 pub fn start_ui() {
+    inference_backend::init_backend(inference_backend::BackendKind::from_env());
+    communication::load_root();
+    image_data_wrapper::load_base_weights_dir();
+
+    // Übrig gebliebene Ordner von abgebrochenen Vorhersagen aufräumen
+    match communication::collect_stale_workspaces(communication::STALE_AFTER) {
+        Ok(0) => {}
+        Ok(n) => println!("{} alte Communication Ordner gelöscht", n),
+        Err(e) => eprintln!("Fehler beim Aufräumen von Communication: {:?}", e),
+    }
+
     let options = eframe::NativeOptions::default();
     let _ = eframe::run_native(
         "Screenshot Tool",
@@ -56,21 +70,40 @@ use std::{i128, vec};
 struct GetBuildingsThread {
     path_to_image: String,
     buildings: Result<Vec<image_data_wrapper::Building>, FofError>,
+    native_buildings: Result<Vec<image_data_wrapper::Building>, FofError>,
     model_name: String,
     should_get_prediction: bool,
+    compare_native: bool,
+    tiling: Option<split_image::TileSettings>, // None = ganzes Bild auf einmal
 }
 
 impl threading::AutoThread for GetBuildingsThread {
     fn run(&mut self) {
         if self.should_get_prediction {
-            self.buildings =
-                image_data_wrapper::get_prediction(&self.model_name.clone(), &self.path_to_image);
+            self.buildings = match &self.tiling {
+                Some(settings) => image_data_wrapper::get_tiled_prediction(
+                    &self.model_name.clone(),
+                    &self.path_to_image,
+                    settings,
+                ),
+                None => image_data_wrapper::get_prediction(
+                    &self.model_name.clone(),
+                    &self.path_to_image,
+                ),
+            };
+            if self.compare_native {
+                self.native_buildings = native_inference::get_native_prediction(
+                    &self.model_name.clone(),
+                    &self.path_to_image,
+                );
+            }
             self.should_get_prediction = false;
         }
     }
     fn handle_field_get(&self, field: &str) -> Option<Box<dyn std::any::Any + Send>> {
         auto_get_field!(self, field, {
             "buildings" => buildings: Result<Vec<image_data_wrapper::Building>, FofError>,
+            "native_buildings" => native_buildings: Result<Vec<image_data_wrapper::Building>, FofError>,
             "model_name" => model_name: String,
             "path_to_image" => path_to_image: String,
         })
@@ -80,7 +113,10 @@ impl threading::AutoThread for GetBuildingsThread {
             "model_name" => model_name: String,
             "path_to_image"=> path_to_image: String,
             "buildings" => buildings: Result<Vec<image_data_wrapper::Building>, FofError>,
-            "should_get_prediction" => should_get_prediction: bool
+            "native_buildings" => native_buildings: Result<Vec<image_data_wrapper::Building>, FofError>,
+            "should_get_prediction" => should_get_prediction: bool,
+            "compare_native" => compare_native: bool,
+            "tiling" => tiling: Option<split_image::TileSettings>
         })
     }
 }
@@ -185,78 +221,441 @@ enum Keybind {
     Done(Key),
 }
 
-struct TrainThread {
-    child: Option<Result<Child, FofError>>,
-    model_name: Option<String>,
-    epochen: Option<usize>,
-    msg: Option<String>,
+// So viele Klassen mit dem schlechtesten mAP50-95 werden in der TestVals Tabelle rot markiert
+const WEAKEST_CLASSES: usize = 5;
+
+/// Spalte, nach der die TestVals Tabelle sortiert wird.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ClassSort {
+    Name,
+    Images,
+    Instances,
+    Precision,
+    Recall,
+    Map50,
+    Map50_95,
 }
-impl TrainThread {
-    fn new() -> TrainThread {
-        TrainThread {
-            child: None,
-            epochen: None,
-            model_name: None,
-            msg: None,
-        }
-    }
-    fn start(&mut self, epochen: usize, model_name: String) {
-        self.epochen = Some(epochen);
-        self.model_name = Some(model_name);
-
-        if let Some(epochen) = self.epochen {
-            if let Some(model_name) = &self.model_name {
-                self.child = Some(image_data_wrapper::start_training(
-                    model_name.as_str(),
-                    epochen as i32,
-                ));
-            } else {
-                self.msg = Some(String::from("Model Name ist nicht definiert"));
-                self.stop();
-            }
-        } else {
-            self.msg = Some(String::from("Epochen sind nicht definiert"));
-            self.stop();
+
+impl ClassSort {
+    fn compare(
+        &self,
+        a: &image_data_wrapper::ClassMetrics,
+        b: &image_data_wrapper::ClassMetrics,
+    ) -> std::cmp::Ordering {
+        let f = |x: f64, y: f64| x.partial_cmp(&y).unwrap_or(std::cmp::Ordering::Equal);
+        match self {
+            ClassSort::Name => a.class_name.cmp(&b.class_name),
+            ClassSort::Images => a.images.cmp(&b.images),
+            ClassSort::Instances => a.instances.cmp(&b.instances),
+            ClassSort::Precision => f(a.precision, b.precision),
+            ClassSort::Recall => f(a.recall, b.recall),
+            ClassSort::Map50 => f(a.map_50, b.map_50),
+            ClassSort::Map50_95 => f(a.map_50_95, b.map_50_95),
+        }
+    }
+}
+
+/// Model erstellen dauert (Download + erstes Training), deshalb im Hintergrund.
+struct CreateModelJob {
+    model_name: String,
+    result: std::sync::mpsc::Receiver<Result<(), FofError>>,
+}
+
+impl CreateModelJob {
+    fn start(
+        model_name: String,
+        dataset_type: image_data_wrapper::DatasetType,
+        yolo_model: image_data_wrapper::YoloModel,
+        options: image_data_wrapper::CreateModelOptions,
+    ) -> CreateModelJob {
+        let (tx, result) = std::sync::mpsc::channel();
+
+        let thread_model = model_name.clone();
+        thread::spawn(move || {
+            let res =
+                image_data_wrapper::create_model(&thread_model, dataset_type, yolo_model, &options);
+            let _ = tx.send(res);
+        });
+
+        CreateModelJob { model_name, result }
+    }
+
+    /// `None` solange Python noch läuft.
+    fn poll(&self) -> Option<Result<(), FofError>> {
+        match self.result.try_recv() {
+            Ok(res) => Some(res),
+            Err(std::sync::mpsc::TryRecvError::Empty) => None,
+            Err(std::sync::mpsc::TryRecvError::Disconnected) => Some(Err(FofError::Failed(
+                "Thread zum Erstellen ist abgestürzt".to_string(),
+            ))),
         }
     }
+}
 
-    fn is_running(&mut self) -> bool {
-        if let Some(child) = self.child.as_mut() {
-            if let Ok(child) = child {
-                if let Ok(Some(_)) = child.try_wait() {
-                    return false;
-                } else if let Err(e) = child.try_wait() {
-                    self.msg = Some(format!("Error while trying to check Child: {:?}", e));
-                    return false;
-                } else {
-                    return true;
+/// ONNX Export startet Python und lädt ultralytics, deshalb im Hintergrund wie `CreateModelJob`.
+struct OnnxExportJob {
+    model_name: String,
+    result: std::sync::mpsc::Receiver<Result<(), FofError>>,
+}
+
+impl OnnxExportJob {
+    fn start(model_name: String) -> OnnxExportJob {
+        let (tx, result) = std::sync::mpsc::channel();
+
+        let thread_model = model_name.clone();
+        thread::spawn(move || {
+            let _ = tx.send(native_inference::export_onnx(&thread_model));
+        });
+
+        OnnxExportJob { model_name, result }
+    }
+
+    /// `None` solange Python noch läuft.
+    fn poll(&self) -> Option<Result<(), FofError>> {
+        match self.result.try_recv() {
+            Ok(res) => Some(res),
+            Err(std::sync::mpsc::TryRecvError::Empty) => None,
+            Err(std::sync::mpsc::TryRecvError::Disconnected) => Some(Err(FofError::Failed(
+                "Thread für den ONNX Export ist abgestürzt".to_string(),
+            ))),
+        }
+    }
+}
+
+/// Vorhersagen für die ausgewählten Bilder im Hintergrund, eine JSON pro Bild.
+struct BatchPrediction {
+    model_name: String,
+    output_dir: PathBuf,
+    progress: std::sync::mpsc::Receiver<image_data_wrapper::BatchProgress>,
+    // Fehler, bevor überhaupt ein Bild dran war (z. B. Modell nicht gefunden)
+    result: std::sync::mpsc::Receiver<Result<usize, FofError>>,
+    cancel: Arc<AtomicBool>,
+    done: usize,
+    total: usize,
+    succeeded: usize,
+    failed: usize,
+    error: Option<FofError>,
+    finished: bool,
+}
+
+impl BatchPrediction {
+    fn start(model_name: String, images: Vec<String>, output_dir: PathBuf) -> BatchPrediction {
+        let (tx, progress) = std::sync::mpsc::channel();
+        let (result_tx, result) = std::sync::mpsc::channel();
+        let cancel = Arc::new(AtomicBool::new(false));
+        let total = images.len();
+
+        let thread_cancel = cancel.clone();
+        let thread_model = model_name.clone();
+        let thread_output = output_dir.clone();
+        thread::spawn(move || {
+            let res = image_data_wrapper::get_predictions(
+                &thread_model,
+                &images,
+                &thread_cancel,
+                |mut progress| {
+                    if let Ok(buildings) = &progress.result {
+                        if let Err(e) = image_data_wrapper::write_prediction_json(
+                            &thread_output,
+                            &progress.image_path,
+                            buildings,
+                        ) {
+                            progress.result = Err(e);
+                        }
+                    }
+                    let _ = tx.send(progress);
+                },
+            );
+
+            let _ = result_tx.send(res);
+        });
+
+        BatchPrediction {
+            model_name,
+            output_dir,
+            progress,
+            result,
+            cancel,
+            done: 0,
+            total,
+            succeeded: 0,
+            failed: 0,
+            error: None,
+            finished: false,
+        }
+    }
+
+    /// Holt neue Ergebnisse ab und gibt Fehler zurück, die angezeigt werden sollen.
+    fn update(&mut self) -> Vec<String> {
+        let mut errors = vec![];
+
+        loop {
+            match self.progress.try_recv() {
+                Ok(progress) => {
+                    self.done = progress.done;
+                    match progress.result {
+                        Ok(_) => self.succeeded += 1,
+                        Err(e) => {
+                            self.failed += 1;
+                            errors.push(format!("{}: {:?}", progress.image_path, e));
+                        }
+                    }
+                }
+                Err(std::sync::mpsc::TryRecvError::Empty) => break,
+                Err(std::sync::mpsc::TryRecvError::Disconnected) => {
+                    self.finished = true;
+                    break;
                 }
-            } else if let Err(e) = child {
-                self.msg = Some(format!("Error beim Training Starten: {:?}", e));
-                return false;
-            } else {
-                unreachable!();
             }
-        } else {
-            self.msg = Some(String::from("Child ist nicht definiert"));
-            return false;
         }
-        false
+
+        // Der Fortschritts-Kanal ist schon zu, wenn `get_predictions` zurückkommt, das Ergebnis
+        // wird erst danach geschickt. Deshalb hier warten, das dauert höchstens einen Moment.
+        if self.finished {
+            if let Ok(Err(e)) = self.result.recv() {
+                self.error = Some(e);
+            }
+        }
+
+        errors
     }
 
-    fn stop(&mut self) {
-        if let Some(child) = self.child.as_mut() {
-            if let Ok(child) = child {
-                image_data_wrapper::stop_training(child);
-            } else if let Err(e) = child {
-                self.msg = Some(format!("Error beim Training Starten: {:?}", e));
-            } else {
-                unreachable!();
+    fn cancel(&self) {
+        self.cancel.store(true, Ordering::SeqCst);
+    }
+}
+
+// Gebäude, Level und die nativen Varianten, mehr Listen gehen pro Frame nicht durch NMS
+const NMS_CACHE_SIZE: usize = 4;
+
+/// Letzte NMS-Ergebnisse. `filter_detections` läuft jeden Frame, NMS soll aber nur neu
+/// laufen, wenn sich die Vorhersagen oder die NMS-Einstellungen ändern.
+#[derive(Default)]
+struct NmsCache {
+    // (Modus, IoU, Eingabe, Ergebnis), das zuletzt benutzte steht hinten
+    entries: Vec<(
+        filter_buildings::NmsMode,
+        f32,
+        Vec<image_data_wrapper::Building>,
+        Vec<image_data_wrapper::Building>,
+    )>,
+}
+
+impl NmsCache {
+    fn nms(
+        &mut self,
+        buildings: Vec<image_data_wrapper::Building>,
+        mode: filter_buildings::NmsMode,
+        iou: f32,
+    ) -> Vec<image_data_wrapper::Building> {
+        let hit = self
+            .entries
+            .iter()
+            .position(|(m, i, input, _)| *m == mode && *i == iou && *input == buildings);
+
+        let entry = match hit {
+            Some(i) => self.entries.remove(i),
+            None => {
+                let result = filter_buildings::nms(&buildings, mode, iou);
+                (mode, iou, buildings, result)
             }
-        } else {
-            self.msg = Some(String::from("Child ist nicht definiert"));
+        };
+        let result = entry.3.clone();
+
+        self.entries.push(entry);
+        if self.entries.len() > NMS_CACHE_SIZE {
+            self.entries.remove(0);
+        }
+        result
+    }
+}
+
+/// Auto-Tune der Schwellen pro Klasse im Hintergrund (siehe `class_thresholds::auto_tune`).
+struct AutoTuneJob {
+    model_name: String,
+    progress: std::sync::mpsc::Receiver<class_thresholds::TuneProgress>,
+    result: std::sync::mpsc::Receiver<Result<Vec<class_thresholds::ClassTuning>, FofError>>,
+    cancel: Arc<AtomicBool>,
+    done: usize,
+    total: usize,
+}
+
+impl AutoTuneJob {
+    fn start(model_name: String, nms_mode: filter_buildings::NmsMode, nms_iou: f32) -> Self {
+        let (progress_tx, progress) = std::sync::mpsc::channel();
+        let (result_tx, result) = std::sync::mpsc::channel();
+        let cancel = Arc::new(AtomicBool::new(false));
+
+        let thread_cancel = cancel.clone();
+        let thread_model = model_name.clone();
+        thread::spawn(move || {
+            let res = class_thresholds::auto_tune(
+                &thread_model,
+                nms_mode,
+                nms_iou,
+                &thread_cancel,
+                |p| {
+                    let _ = progress_tx.send(p);
+                },
+            );
+            let _ = result_tx.send(res);
+        });
+
+        AutoTuneJob {
+            model_name,
+            progress,
+            result,
+            cancel,
+            done: 0,
+            total: 0,
+        }
+    }
+
+    fn poll(&mut self) -> Option<Result<Vec<class_thresholds::ClassTuning>, FofError>> {
+        while let Ok(p) = self.progress.try_recv() {
+            self.done = p.done;
+            self.total = p.total;
+        }
+
+        match self.result.try_recv() {
+            Ok(res) => Some(res),
+            Err(std::sync::mpsc::TryRecvError::Empty) => None,
+            Err(std::sync::mpsc::TryRecvError::Disconnected) => Some(Err(FofError::Failed(
+                "Auto-Tune Thread ist abgestürzt".to_string(),
+            ))),
+        }
+    }
+}
+
+fn model_label(model: &image_data_wrapper::Model) -> String {
+    let mut label = format!(
+        "{} ({:.2}) Typ: {}",
+        model.name,
+        model.rating,
+        match &model.dataset_type {
+            Some(dataset_type) => dataset_type.display_name(),
+            None => "❓ Unbekannt".to_string(),
         }
+    );
+
+    if model.status != image_data_wrapper::ModelStatus::Trained {
+        label.push_str(&format!(" [{}]", model.status.to_string()));
+    }
+
+    let meta = &model.metadata;
+    if !meta.base_model.is_empty() {
+        label.push_str(&format!(" | {}", meta.base_model));
+    }
+    label.push_str(&format!(" | {} Ep.", meta.total_epochs));
+    if !meta.tags.is_empty() {
+        label.push_str(&format!(" | #{}", meta.tags.join(" #")));
+    }
+
+    label
+}
+
+fn model_tooltip(model: &image_data_wrapper::Model) -> String {
+    let meta = &model.metadata;
+    let mut lines = vec![
+        format!(
+            "Status: {}{}",
+            model.status.to_string(),
+            model
+                .status_reason
+                .as_ref()
+                .map(|r| format!(" ({})", r))
+                .unwrap_or_default()
+        ),
+        format!("Basis: {}", meta.base_model),
+        format!("Erstellt: {}", meta.created_at),
+        format!("Epochen insgesamt: {}", meta.total_epochs),
+    ];
+
+    if let Some(parent) = &meta.parent {
+        lines.push(format!("Abgeleitet von: {}", parent));
+    }
+    if let Some(snapshot) = &meta.dataset_snapshot {
+        lines.push(format!(
+            "Dataset: {} ({} train / {} val, {})",
+            snapshot.data_yaml, snapshot.train_images, snapshot.val_images, snapshot.fingerprint
+        ));
+    }
+    if !meta.notes.is_empty() {
+        lines.push(format!("Notizen: {}", meta.notes));
+    }
+
+    lines.join("\n")
+}
+
+fn format_duration(d: std::time::Duration) -> String {
+    let secs = d.as_secs();
+    format!(
+        "{:02}:{:02}:{:02}",
+        secs / 3600,
+        (secs / 60) % 60,
+        secs % 60
+    )
+}
+
+fn show_training_progress(ui: &mut egui::Ui, progress: &training_progress::TrainingProgress) {
+    use egui_plot::{Legend, Line, Plot, PlotPoints};
+
+    let current = progress.current_epoch();
+    let fraction = if progress.total_epochs == 0 {
+        0.0
+    } else {
+        current as f32 / progress.total_epochs as f32
+    };
+
+    ui.add(
+        egui::ProgressBar::new(fraction)
+            .text(format!("Epoche {}/{}", current, progress.total_epochs)),
+    );
+
+    ui.horizontal(|ui| {
+        ui.label(format!("⏱ {}", format_duration(progress.elapsed())));
+        match progress.eta() {
+            Some(eta) => ui.label(format!("ETA: {}", format_duration(eta))),
+            None => ui.label("ETA: warte auf erste Epoche..."),
+        };
+    });
+
+    if let Some(latest) = progress.latest() {
+        ui.label(format!(
+            "box: {:.4}  cls: {:.4}  dfl: {:.4}  |  mAP50: {:.3}  mAP50-95: {:.3}",
+            latest.box_loss, latest.cls_loss, latest.dfl_loss, latest.map_50, latest.map_50_95
+        ));
     }
+
+    let series = |f: fn(&image_data_wrapper::EpochMetrics) -> f64| -> PlotPoints {
+        progress
+            .epochs
+            .iter()
+            .map(|e| [e.epoch as f64, f(e)])
+            .collect()
+    };
+
+    Plot::new(format!("training_progress_{}", progress.model_name))
+        .height(200.0)
+        .legend(Legend::default())
+        .show(ui, |plot_ui| {
+            plot_ui.line(Line::new("box_loss", series(|e| e.box_loss)));
+            plot_ui.line(Line::new("cls_loss", series(|e| e.cls_loss)));
+            plot_ui.line(Line::new("dfl_loss", series(|e| e.dfl_loss)));
+            plot_ui.line(Line::new("mAP50", series(|e| e.map_50)));
+            plot_ui.line(Line::new("mAP50-95", series(|e| e.map_50_95)));
+        });
+
+    ui.collapsing("Log", |ui| {
+        egui::ScrollArea::vertical()
+            .max_height(200.0)
+            .stick_to_bottom(true)
+            .show(ui, |ui| {
+                for line in progress.log_lines.iter() {
+                    ui.monospace(line);
+                }
+            });
+    });
 }
 
 // new enum
@@ -279,6 +678,16 @@ pub struct ScreenshotApp {
     pub connect_walls_enabled: bool, // default false
     pub min_dist_to_connect: f32,    // default 32.0
     pub min_iou: f32,
+    pub use_class_thresholds: bool,
+    pub class_thresholds: HashMap<String, class_thresholds::ClassThresholds>, // pro Modell
+    class_names_for_thresholds: HashMap<String, Vec<String>>,
+    auto_tune_job: Option<AutoTuneJob>,
+    auto_tune_results: HashMap<String, Vec<class_thresholds::ClassTuning>>,
+    pub nms_mode: filter_buildings::NmsMode,
+    pub nms_iou: f32, // default 0.5
+    nms_cache: std::cell::RefCell<NmsCache>,
+    pub tiled_prediction: bool,
+    pub tile_settings: split_image::TileSettings,
     pub angle_variance: f32,
     pub find_hidden_walls_enabled: bool, // default false
     pub combine_models_enabled: bool,    // no-op for now
@@ -293,6 +702,9 @@ pub struct ScreenshotApp {
     current_buildings_build: Option<Vec<image_data_wrapper::Building>>,
     get_building_thread_lvls: threading::WorkerHandle<GetBuildingsThread>,
     current_buildings_lvls: Option<Vec<image_data_wrapper::Building>>,
+    compare_native: bool, // ONNX in Rust zusätzlich zu Python laufen lassen
+    current_native_build: Option<Vec<image_data_wrapper::Building>>,
+    current_native_lvls: Option<Vec<image_data_wrapper::Building>>,
 
     // labels / UI state
     pub label_mode: LabelMode, // default: LabelMode::ClassName
@@ -315,7 +727,51 @@ pub struct ScreenshotApp {
     messages: Vec<UiMessage>,
     labeling_que: Vec<String>,
     selected_images: HashSet<String>,
-    train_threads: Vec<TrainThread>,
+    training_queue: training_queue::TrainingQueue,
+    training_config: image_data_wrapper::TrainingConfig,
+    training_presets: Vec<(String, image_data_wrapper::TrainingConfig)>,
+    new_preset_name: String,
+    batch_prediction: Option<BatchPrediction>,
+    create_model_job: Option<CreateModelJob>,
+    yolo_model_list: Option<Vec<(image_data_wrapper::YoloModel, bool)>>, // Modell, lokal
+    onnx_export_job: Option<OnnxExportJob>,
+    create_model_options: image_data_wrapper::CreateModelOptions,
+    metadata_edit: Option<(String, image_data_wrapper::ModelMetadata)>, // Modellname + bearbeitete Kopie
+    metadata_tags: String,
+    overview_models: Vec<String>, // Reihenfolge = Farbe im Chart
+    overview_metrics: HashSet<image_data_wrapper::MetricColumn>,
+    overview_histories: HashMap<String, Result<Vec<image_data_wrapper::EpochMetrics>, FofError>>,
+    class_metrics: Option<(
+        String,
+        Result<Vec<image_data_wrapper::ClassMetrics>, FofError>,
+    )>,
+    class_metrics_sort: ClassSort,
+    class_metrics_ascending: bool,
+    rating_formula: image_data_wrapper::RatingFormula,
+    fork_model_name: String,
+    fork_train_after: bool,
+    fork_train_epochs: usize,
+    ensemble_name: String,
+    ensemble_dataset: Option<String>,
+    ensemble_weights: HashMap<String, f32>, // gewählte Mitglieder mit Gewicht
+    ensemble_iou: f32,
+    sweeps: Vec<sweep::Sweep>,
+    sweep_name: String,
+    sweep_epochen: usize,
+    sweep_values: HashMap<sweep::SweepParam, String>,
+    sweep_enabled: HashSet<sweep::SweepParam>,
+    sweep_random: Option<usize>, // None = Grid
+    selected_sweep: Option<String>,
+    sweep_results: Option<(String, std::time::Instant, Vec<sweep::TrialResult>)>,
+    sweep_promote_name: String,
+    prediction_cache_stats: Option<prediction_cache::CacheStats>,
+    selected_log: Option<PathBuf>,
+    log_list: Option<(String, Vec<run_log::RunLogEntry>)>, // Modell, Logs
+    log_reader: Option<run_log::LogReader>,
+    log_refreshed: Option<std::time::Instant>,
+    log_search: String,
+    log_auto_scroll: bool,
+    rating_class_names: Option<Vec<(DatasetType, Result<Vec<(u32, String)>, FofError>)>>,
     active_tab: Tab,
     labeled_rects: Vec<SmthLabeled>,
     current_rect_start: Option<egui::Pos2>,
@@ -543,6 +999,16 @@ impl Default for ScreenshotApp {
     fn default() -> Self {
         let mut s = Self {
             min_confidence: 0.25,
+            use_class_thresholds: true,
+            class_thresholds: HashMap::new(),
+            class_names_for_thresholds: HashMap::new(),
+            auto_tune_job: None,
+            auto_tune_results: HashMap::new(),
+            nms_mode: filter_buildings::NmsMode::ClassAware,
+            nms_iou: 0.5,
+            nms_cache: Default::default(),
+            tiled_prediction: false,
+            tile_settings: split_image::TileSettings::default(),
             threshold45deg: 10.0, // wie nah müssen Mauern in y sein, um in einer Reihe zu sein
             max_gap: 15.0, // max Abstand zwischen Mauern, um Lücke als potenzielle Mauer zu sehen
             building_y_threshold: 20.0, // max vertikaler Abstand Gebäude unter Mauerreihe
@@ -561,12 +1027,18 @@ impl Default for ScreenshotApp {
             selected_lvls_model: None,
             current_buildings_build: None,
             current_buildings_lvls: None,
+            compare_native: false,
+            current_native_build: None,
+            current_native_lvls: None,
             get_building_thread_build: threading::WorkerHandle::start(
                 GetBuildingsThread {
                     path_to_image: "".to_string(),
                     buildings: Err(FofError::ThreadNotInitialized),
+                    native_buildings: Err(FofError::ThreadNotInitialized),
                     model_name: "".to_string(),
                     should_get_prediction: false,
+                    compare_native: false,
+                    tiling: None,
                 },
                 true,
             ),
@@ -575,8 +1047,11 @@ impl Default for ScreenshotApp {
                 GetBuildingsThread {
                     path_to_image: "".to_string(),
                     buildings: Err(FofError::ThreadNotInitialized),
+                    native_buildings: Err(FofError::ThreadNotInitialized),
                     model_name: "".to_string(),
                     should_get_prediction: false,
+                    compare_native: false,
+                    tiling: None,
                 },
                 true,
             ),
@@ -603,7 +1078,58 @@ impl Default for ScreenshotApp {
             messages: vec![],
             labeling_que: vec![],
             selected_images: HashSet::new(),
-            train_threads: vec![],
+            training_queue: training_queue::TrainingQueue::load(training_queue::QUEUE_FILE)
+                .unwrap_or_else(|e| {
+                    eprintln!("Konnte Trainings-Warteschlange nicht laden: {:?}", e);
+                    training_queue::TrainingQueue::new(training_queue::QUEUE_FILE)
+                }),
+            training_config: image_data_wrapper::TrainingConfig::default(),
+            training_presets: image_data_wrapper::TrainingConfig::load_presets(),
+            new_preset_name: String::new(),
+            batch_prediction: None,
+            create_model_job: None,
+            yolo_model_list: None,
+            onnx_export_job: None,
+            create_model_options: image_data_wrapper::CreateModelOptions::default(),
+            metadata_edit: None,
+            metadata_tags: String::new(),
+            overview_models: vec![],
+            overview_metrics: HashSet::from([image_data_wrapper::MetricColumn::Map50_95]),
+            overview_histories: HashMap::new(),
+            class_metrics: None,
+            class_metrics_sort: ClassSort::Map50_95,
+            class_metrics_ascending: true,
+            rating_formula: image_data_wrapper::RatingFormula::load(),
+            fork_model_name: String::new(),
+            fork_train_after: true,
+            ensemble_name: String::new(),
+            ensemble_dataset: None,
+            ensemble_weights: HashMap::new(),
+            ensemble_iou: 0.55,
+            fork_train_epochs: 50,
+            sweeps: sweep::Sweep::load_all(),
+            sweep_name: String::new(),
+            sweep_epochen: 20,
+            sweep_values: HashMap::from([
+                (sweep::SweepParam::Imgsz, "640, 960".to_string()),
+                (sweep::SweepParam::Lr0, "0.01, 0.005".to_string()),
+                (sweep::SweepParam::Mosaic, "1.0, 0.5".to_string()),
+                (sweep::SweepParam::Scale, "0.5, 0.3".to_string()),
+                (sweep::SweepParam::Batch, "4, 8".to_string()),
+            ]),
+            sweep_enabled: HashSet::from([sweep::SweepParam::Imgsz, sweep::SweepParam::Lr0]),
+            sweep_random: None,
+            selected_sweep: None,
+            sweep_results: None,
+            sweep_promote_name: String::new(),
+            prediction_cache_stats: None,
+            selected_log: None,
+            log_list: None,
+            log_reader: None,
+            log_refreshed: None,
+            log_search: String::new(),
+            log_auto_scroll: true,
+            rating_class_names: None,
 
             active_tab: Tab::Settings,
             labeled_rects: vec![],
@@ -787,10 +1313,11 @@ impl ScreenshotApp {
             false
         }
 
-        let train_path = Path::new("dataset_buildings/images/train");
-        let val_path = Path::new("dataset_buildings/images/val");
-
-        file_matches(train_path, filename) || file_matches(val_path, filename)
+        DatasetType::labeling_targets().iter().any(|dataset_type| {
+            let dir = dataset_type.dir();
+            file_matches(&Path::new(&dir).join("images/train"), filename)
+                || file_matches(&Path::new(&dir).join("images/val"), filename)
+        })
     }
 
     pub fn create_error(&mut self, msg: impl Into<String>, kind: MessageType) {
@@ -1009,6 +1536,91 @@ impl ScreenshotApp {
                 "📁 Ausgewähter Speicher Ordner: {}",
                 self.screenshot_path
             ));
+
+            ui.label(format!(
+                "🧠 Backend: {}",
+                inference_backend::backend().name()
+            ));
+
+            if ui
+                .button("📂 Ordner für Vorhersage-Dateien wählen")
+                .clicked()
+            {
+                if let Some(path) = rfd::FileDialog::new().pick_folder() {
+                    match communication::set_root(path) {
+                        Ok(_) => {
+                            self.create_error("Communication Ordner Geändert", MessageType::Success)
+                        }
+                        Err(e) => self.create_error(
+                            format!("Communication Ordner nicht gespeichert: {:?}", e),
+                            MessageType::Error,
+                        ),
+                    }
+                }
+            }
+
+            ui.label(format!(
+                "📁 Communication Ordner: {}",
+                communication::root().display()
+            ));
+
+            if ui.button("📂 Ordner für Basis-Gewichte wählen").clicked() {
+                if let Some(path) = rfd::FileDialog::new().pick_folder() {
+                    match image_data_wrapper::set_base_weights_dir(path) {
+                        Ok(_) => self
+                            .create_error("Basis-Gewichte Ordner Geändert", MessageType::Success),
+                        Err(e) => self.create_error(
+                            format!("Basis-Gewichte Ordner nicht gespeichert: {:?}", e),
+                            MessageType::Error,
+                        ),
+                    }
+                }
+            }
+
+            ui.label(format!(
+                "📁 Basis-Gewichte Ordner: {}",
+                image_data_wrapper::base_weights_dir().display()
+            ));
+        });
+        ui.separator();
+        ui.collapsing("Vorhersage-Cache", |ui| {
+            let stats = *self
+                .prediction_cache_stats
+                .get_or_insert_with(prediction_cache::stats);
+            ui.label(format!(
+                "{} Vorhersagen, {:.1} MB (max. {} MB) in {}",
+                stats.entries,
+                stats.bytes as f64 / 1024.0 / 1024.0,
+                prediction_cache::MAX_CACHE_BYTES / 1024 / 1024,
+                prediction_cache::CACHE_DIR
+            ));
+
+            ui.horizontal(|ui| {
+                if ui.button("Aktualisieren").clicked() {
+                    self.prediction_cache_stats = None;
+                }
+
+                if ui
+                    .add(
+                        egui::Button::new(RichText::new("Cache leeren").color(Color32::WHITE))
+                            .fill(RED),
+                    )
+                    .clicked()
+                {
+                    match prediction_cache::clear_all() {
+                        Ok(_) => self.create_error("Cache geleert", MessageType::Success),
+                        Err(e) => self.create_error(
+                            format!("Konnte Cache nicht leeren: {:?}", e),
+                            MessageType::Error,
+                        ),
+                    }
+                    self.prediction_cache_stats = None;
+                }
+            });
+        });
+        ui.separator();
+        ui.collapsing("Rating Formel", |ui| {
+            self.rating_formula_ui(ui);
         });
         ui.separator();
         ui.collapsing("Keybinds", |ui| {
@@ -1067,6 +1679,106 @@ impl ScreenshotApp {
         });
     }
 
+    fn rating_formula_ui(&mut self, ui: &mut egui::Ui) {
+        ui.label("Rating = a·mAP50-95 + b·mAP50 + c·Precision + d·Recall");
+
+        egui::Grid::new("rating_formula_grid").show(ui, |ui| {
+            let formula = &mut self.rating_formula;
+            for (label, value) in [
+                ("a (mAP50-95)", &mut formula.weight_map_50_95),
+                ("b (mAP50)", &mut formula.weight_map_50),
+                ("c (Precision)", &mut formula.weight_precision),
+                ("d (Recall)", &mut formula.weight_recall),
+            ] {
+                ui.label(label);
+                ui.add(Slider::new(value, 0.0..=1.0));
+                ui.end_row();
+            }
+        });
+
+        ui.checkbox(
+            &mut self.rating_formula.per_class,
+            "Pro Klasse rechnen (mit Klassen-Gewichten)",
+        )
+        .on_hover_text(
+            "Benutzt die Klassen-Metriken vom letzten TestVals Lauf. \
+             Modelle ohne TestVals werden weiter über results.csv bewertet.",
+        );
+
+        if self.rating_formula.per_class {
+            let class_names = self.rating_class_names.get_or_insert_with(|| {
+                DatasetType::all()
+                    .into_iter()
+                    .map(|d| {
+                        let names = image_data_wrapper::read_class_names(&d);
+                        (d, names)
+                    })
+                    .collect()
+            });
+
+            for (dataset_type, names) in class_names.iter() {
+                ui.collapsing(format!("Gewichte {}", dataset_type.to_string()), |ui| {
+                    let names = match names {
+                        Ok(names) => names,
+                        Err(e) => {
+                            ui.label(RichText::new(format!("{:?}", e)).color(RED));
+                            return;
+                        }
+                    };
+
+                    egui::ScrollArea::vertical()
+                        .id_salt(format!("rating_weights_{}", dataset_type.to_string()))
+                        .max_height(300.0)
+                        .show(ui, |ui| {
+                            egui::Grid::new(format!(
+                                "rating_weights_grid_{}",
+                                dataset_type.to_string()
+                            ))
+                            .striped(true)
+                            .show(ui, |ui| {
+                                for (_, name) in names {
+                                    let mut weight = self.rating_formula.class_weight(name);
+                                    ui.label(name);
+                                    if ui
+                                        .add(
+                                            egui::DragValue::new(&mut weight)
+                                                .speed(0.1)
+                                                .range(0.0..=100.0),
+                                        )
+                                        .changed()
+                                    {
+                                        self.rating_formula
+                                            .class_weights
+                                            .insert(name.clone(), weight);
+                                    }
+                                    ui.end_row();
+                                }
+                            });
+                        });
+                });
+            }
+        }
+
+        ui.horizontal(|ui| {
+            if ui.button("💾 Speichern").clicked() {
+                match self.rating_formula.save() {
+                    Ok(_) => {
+                        self.reload_models();
+                        self.create_error("Rating Formel gespeichert", MessageType::Success);
+                    }
+                    Err(e) => self.create_error(
+                        format!("Konnte Rating Formel nicht speichern: {:?}", e),
+                        MessageType::Error,
+                    ),
+                }
+            }
+
+            if ui.button("Zurücksetzen").clicked() {
+                self.rating_formula = image_data_wrapper::RatingFormula::default();
+            }
+        });
+    }
+
     fn take_labeled_screenshot(&mut self) {
         let now = Local::now();
         let filename = format!("{}.png", now.format("%Y-%m-%d_%H-%M-%S"));
@@ -1266,7 +1978,8 @@ impl ScreenshotApp {
         }
     }
 
-    fn show_selectable_models(&mut self, ui: &mut egui::Ui) {
+    /// Mit `only_usable` sind untrainierte / kaputte Modelle ausgegraut (z. B. für Vorhersagen).
+    fn show_selectable_models(&mut self, ui: &mut egui::Ui, only_usable: bool) {
         self.current_models.sort_by(|a, b| {
             a.rating
                 .partial_cmp(&b.rating)
@@ -1282,18 +1995,20 @@ impl ScreenshotApp {
             .show_ui(ui, |ui| {
                 for model in self.current_models.clone() {
                     let score = model.rating;
-                    let name = model.name;
+                    let name = model.name.clone();
 
-                    let label = format!(
-                        "{name} ({score:.2}) Typ: {}",
-                        match model.dataset_type {
-                            image_data_wrapper::DatasetType::Buildings => "🏗️ Building Model",
-                            image_data_wrapper::DatasetType::Level => "🎯 Level Model",
-                        }
-                    );
+                    let label = model_label(&model);
+                    let enabled = !only_usable || model.is_usable();
 
                     if ui
-                        .selectable_label(self.selected_model.as_deref() == Some(&name), label)
+                        .add_enabled_ui(enabled, |ui| {
+                            ui.selectable_label(
+                                self.selected_model.as_deref() == Some(&name),
+                                label,
+                            )
+                        })
+                        .inner
+                        .on_hover_text(model_tooltip(&model))
                         .clicked()
                     {
                         self.selected_model = Some(name.clone());
@@ -1304,26 +2019,72 @@ impl ScreenshotApp {
     }
 
     fn show_selectable_yolo_models(&mut self, ui: &mut egui::Ui) {
-        egui::ComboBox::from_label("YOLO-Modell wählen")
-            .selected_text(
-                self.selected_yolo_model
-                    .as_ref()
-                    .map(|m| m.to_string())
-                    .unwrap_or_else(|| "Keins gewählt".to_owned()),
-            )
-            .show_ui(ui, |ui| {
-                for model in image_data_wrapper::YoloModel::iter() {
-                    let is_selected = Some(&model) == self.selected_yolo_model.as_ref();
+        ui.horizontal(|ui| {
+            let closed = egui::ComboBox::from_label("YOLO-Modell wählen")
+                .selected_text(
+                    self.selected_yolo_model
+                        .as_ref()
+                        .map(|m| m.to_string())
+                        .unwrap_or_else(|| "Keins gewählt".to_owned()),
+                )
+                .show_ui(ui, |ui| {
+                    // Einmal pro Öffnen suchen, nicht jeden Frame
+                    let models = self
+                        .yolo_model_list
+                        .get_or_insert_with(|| {
+                            image_data_wrapper::YoloModel::builtin()
+                                .into_iter()
+                                .chain(image_data_wrapper::YoloModel::discover())
+                                .map(|m| {
+                                    let local = m.is_local();
+                                    (m, local)
+                                })
+                                .collect()
+                        })
+                        .clone();
+
+                    for (model, local) in models {
+                        let is_selected = Some(&model) == self.selected_yolo_model.as_ref();
+
+                        let mut label = model.to_string();
+                        if local {
+                            label.push_str(" (lokal)");
+                        }
 
-                    if ui
-                        .selectable_label(is_selected, model.to_string())
-                        .clicked()
-                    {
-                        self.selected_yolo_model = Some(model.clone());
-                        self.create_error(format!("Yolo Model geändert",), MessageType::Success);
+                        if ui.selectable_label(is_selected, label).clicked() {
+                            self.selected_yolo_model = Some(model.clone());
+                            self.create_error(
+                                format!("Yolo Model geändert",),
+                                MessageType::Success,
+                            );
+                        }
                     }
+                })
+                .inner
+                .is_none();
+
+            // Zu = beim nächsten Öffnen neu suchen (neue Dateien, anderer Ordner)
+            if closed {
+                self.yolo_model_list = None;
+            }
+
+            if ui
+                .button("📂 .pt Datei...")
+                .on_hover_text(format!(
+                    "Eigene Basis-Gewichte. Dateien in {} werden automatisch gefunden.",
+                    image_data_wrapper::base_weights_dir().display()
+                ))
+                .clicked()
+            {
+                if let Some(path) = rfd::FileDialog::new()
+                    .add_filter("PyTorch", &["pt"])
+                    .pick_file()
+                {
+                    self.selected_yolo_model = Some(image_data_wrapper::YoloModel::Custom(path));
+                    self.create_error(format!("Yolo Model geändert",), MessageType::Success);
                 }
-            });
+            }
+        });
     }
 
     pub fn ui_dataset_mode_dropdown(&mut self, ui: &mut egui::Ui) {
@@ -1331,27 +2092,38 @@ impl ScreenshotApp {
             ui.label("Datensatztyp:");
 
             let resp = egui::ComboBox::from_id_source("dataset_mode_selector")
-                .selected_text(match self.dataset_mode {
-                    None => "Nicht ausgewählt",
-                    Some(image_data_wrapper::DatasetType::Buildings) => "Building Model",
-                    Some(image_data_wrapper::DatasetType::Level) => "Level Model",
+                .selected_text(match &self.dataset_mode {
+                    None => "Nicht ausgewählt".to_string(),
+                    Some(dataset_type) => dataset_type.display_name(),
                 })
                 .show_ui(ui, |ui| {
-                    ui.selectable_value(
-                        &mut self.dataset_mode,
-                        Some(image_data_wrapper::DatasetType::Buildings),
-                        "Building Model",
-                    );
-                    ui.selectable_value(
-                        &mut self.dataset_mode,
-                        Some(image_data_wrapper::DatasetType::Level),
-                        "Level Model",
-                    );
+                    for dataset_type in DatasetType::all() {
+                        let text = dataset_type.display_name();
+                        let data_yaml = dataset_type.data_yaml();
+                        ui.selectable_value(&mut self.dataset_mode, Some(dataset_type), text)
+                            .on_hover_text(data_yaml);
+                    }
                 });
 
             if resp.response.changed() {
                 self.create_error("Datensatztyp geändert", MessageType::Success);
             }
+
+            if ui
+                .button("🔄")
+                .on_hover_text(format!(
+                    "{}, dataset_* Ordner und {} neu lesen",
+                    image_data_wrapper::DATASETS_FILE,
+                    ensemble::ENSEMBLES_FILE
+                ))
+                .clicked()
+            {
+                DatasetType::reload();
+                ensemble::Ensemble::reload();
+                self.rating_class_names = None;
+                self.reload_models();
+                self.create_error("Datensätze neu geladen", MessageType::Success);
+            }
         });
     }
 
@@ -1373,14 +2145,55 @@ impl ScreenshotApp {
             ui.group(|ui: &mut egui::Ui| {
                 ui.heading("Neues Model Erstellen");
                 ui.separator();
-                ui.horizontal(|ui: &mut egui::Ui| {
-                    ui.label("Model name: ");
-                    ui.text_edit_singleline(&mut self.new_model_name);
-                });
-                self.show_selectable_yolo_models(ui);
-                self.ui_dataset_mode_dropdown(ui);
 
-                if let Some(yolo_model) = &self.selected_yolo_model {
+                if let Some(job) = &self.create_model_job {
+                    match job.poll() {
+                        None => {
+                            ui.horizontal(|ui| {
+                                ui.spinner();
+                                ui.label(format!("Erstelle {}...", job.model_name));
+                            });
+                            return;
+                        }
+                        Some(Ok(_)) => {
+                            let msg = format!("Neues Model {} Erstellt", job.model_name);
+                            self.create_model_job = None;
+                            self.create_error(msg, MessageType::Success);
+                            self.reload_models();
+                        }
+                        Some(Err(e)) => {
+                            let msg = format!(
+                                "Model {} konnte nicht erstellt werden: {:?}",
+                                job.model_name, e
+                            );
+                            self.create_model_job = None;
+                            self.create_error(msg, MessageType::Error);
+                            self.reload_models();
+                        }
+                    }
+                }
+
+                ui.horizontal(|ui: &mut egui::Ui| {
+                    ui.label("Model name: ");
+                    ui.text_edit_singleline(&mut self.new_model_name);
+                });
+                self.show_selectable_yolo_models(ui);
+                self.ui_dataset_mode_dropdown(ui);
+
+                ui.horizontal(|ui| {
+                    ui.label("Erste Epochen:");
+                    ui.add(
+                        egui::DragValue::new(&mut self.create_model_options.epochs).range(1..=1000),
+                    );
+                    ui.label("Bildgröße:");
+                    ui.add(
+                        egui::DragValue::new(&mut self.create_model_options.imgsz)
+                            .range(32..=1920)
+                            .speed(32),
+                    );
+                });
+
+                if let Some(yolo_model) = &self.selected_yolo_model {
                     if !self.new_model_name.is_empty() {
                         if let Some(datamode) = self.dataset_mode.clone() {
                             let button_text =
@@ -1391,28 +2204,39 @@ impl ScreenshotApp {
                                 .stroke(egui::Stroke::new(1.0, Color32::DARK_GREEN)); // optionaler Rand
 
                             if ui.add(button).clicked() {
-                                image_data_wrapper::create_model(
-                                    self.new_model_name.as_str(),
+                                self.create_model_job = Some(CreateModelJob::start(
+                                    self.new_model_name.clone(),
                                     datamode,
                                     yolo_model.clone(),
-                                );
+                                    self.create_model_options.clone(),
+                                ));
                                 self.new_model_name.clear();
                                 self.selected_yolo_model = None;
                                 self.create_error(
-                                    format!("Neues Model Erstellt",),
+                                    format!("Model wird erstellt...",),
                                     MessageType::Success,
                                 );
-                                self.reload_models();
                             }
                         }
                     }
                 }
             });
+            ui.group(|ui: &mut egui::Ui| {
+                ui.heading("Model Forken");
+                ui.separator();
+                self.show_selectable_models(ui, true);
+                self.fork_model_ui(ui);
+            });
+            ui.group(|ui: &mut egui::Ui| {
+                ui.heading("Ensemble Erstellen");
+                ui.separator();
+                self.ensemble_ui(ui);
+            });
             ui.group(|ui: &mut egui::Ui| {
                 ui.heading("Model Löschen");
                 ui.separator();
 
-                self.show_selectable_models(ui);
+                self.show_selectable_models(ui, false);
 
                 if let Some(name) = &self.selected_model {
                     if ui
@@ -1435,7 +2259,298 @@ impl ScreenshotApp {
                     }
                 }
             });
+            ui.group(|ui: &mut egui::Ui| {
+                ui.heading("Model Infos");
+                ui.separator();
+                self.model_metadata_ui(ui);
+            });
+        });
+    }
+
+    fn fork_model_ui(&mut self, ui: &mut egui::Ui) {
+        ui.label("Kopiert weights/best.pt in ein neues Modell, das Original bleibt unverändert.");
+
+        let Some(parent) = self.selected_model.clone() else {
+            ui.label("Kein Modell gewählt");
+            return;
+        };
+
+        ui.label(format!("Fork von: {}", parent));
+
+        ui.horizontal(|ui| {
+            ui.label("Neuer Name:");
+            ui.text_edit_singleline(&mut self.fork_model_name);
+        });
+
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut self.fork_train_after, "Danach trainieren mit");
+            ui.add_enabled(
+                self.fork_train_after,
+                egui::DragValue::new(&mut self.fork_train_epochs).range(1..=10000),
+            );
+            ui.label("Epochen (Hyperparameter aus dem Training-Bereich)");
+        });
+
+        if self.fork_model_name.trim().is_empty() {
+            return;
+        }
+
+        if ui
+            .add(egui::Button::new(RichText::new("Forken").color(Color32::WHITE)).fill(GREEN))
+            .clicked()
+        {
+            let name = self.fork_model_name.trim().to_string();
+
+            if let Err(e) = image_data_wrapper::fork_model(&parent, &name) {
+                self.create_error(
+                    format!("Konnte {} nicht forken: {:?}", parent, e),
+                    MessageType::Error,
+                );
+                return;
+            }
+
+            self.create_error(
+                format!("{} von {} geforkt", name, parent),
+                MessageType::Success,
+            );
+
+            if self.fork_train_after {
+                if let Err(e) = self.training_queue.enqueue(
+                    &name,
+                    self.fork_train_epochs,
+                    self.training_config.clone(),
+                ) {
+                    self.create_error(
+                        format!("Konnte Training nicht einreihen: {:?}", e),
+                        MessageType::Error,
+                    );
+                }
+            }
+
+            self.fork_model_name.clear();
+            self.selected_model = Some(name);
+            self.reload_models();
+        }
+    }
+
+    fn ensemble_ui(&mut self, ui: &mut egui::Ui) {
+        ui.label(
+            "Fasst die Vorhersagen von mehreren Modellen mit Weighted Box Fusion zusammen. \
+             Das Ensemble taucht danach wie ein normales Modell in den Listen auf.",
+        );
+
+        ui.horizontal(|ui| {
+            ui.label("Name:");
+            ui.text_edit_singleline(&mut self.ensemble_name);
+        });
+
+        egui::ComboBox::from_label("Datensatz")
+            .selected_text(
+                self.ensemble_dataset
+                    .as_ref()
+                    .and_then(|n| DatasetType::by_name(n).ok())
+                    .map(|d| d.display_name())
+                    .unwrap_or("Kein Datensatz gewählt".to_string()),
+            )
+            .show_ui(ui, |ui| {
+                for dataset_type in DatasetType::all() {
+                    let selected = self.ensemble_dataset.as_deref() == Some(&dataset_type.name);
+                    if ui
+                        .selectable_label(selected, dataset_type.display_name())
+                        .clicked()
+                        && !selected
+                    {
+                        self.ensemble_dataset = Some(dataset_type.name.clone());
+                        self.ensemble_weights.clear();
+                    }
+                }
+            });
+
+        let Some(dataset) = self.ensemble_dataset.clone() else {
+            return;
+        };
+
+        let candidates: Vec<image_data_wrapper::Model> = self
+            .current_models
+            .iter()
+            .filter(|m| {
+                m.status != image_data_wrapper::ModelStatus::Ensemble
+                    && m.is_usable()
+                    && m.dataset_type.as_ref().map(|d| &d.name) == Some(&dataset)
+            })
+            .cloned()
+            .collect();
+
+        if candidates.len() < 2 {
+            ui.label("Es gibt weniger als zwei nutzbare Modelle für diesen Datensatz");
+            return;
+        }
+
+        egui::Grid::new("ensemble_members_grid").show(ui, |ui| {
+            for model in candidates.iter() {
+                let mut checked = self.ensemble_weights.contains_key(&model.name);
+                if ui
+                    .checkbox(&mut checked, model_label(model))
+                    .on_hover_text(model_tooltip(model))
+                    .changed()
+                {
+                    if checked {
+                        self.ensemble_weights.insert(model.name.clone(), 1.0);
+                    } else {
+                        self.ensemble_weights.remove(&model.name);
+                    }
+                }
+
+                if let Some(weight) = self.ensemble_weights.get_mut(&model.name) {
+                    ui.add(
+                        egui::DragValue::new(weight)
+                            .range(0.0..=10.0)
+                            .speed(0.05)
+                            .prefix("Gewicht: "),
+                    );
+                }
+                ui.end_row();
+            }
         });
+
+        ui.add(
+            egui::Slider::new(&mut self.ensemble_iou, 0.1..=0.9)
+                .step_by(0.05)
+                .text("Fusion IOU"),
+        );
+
+        let can_create = self.ensemble_weights.len() >= 2 && !self.ensemble_name.trim().is_empty();
+        if ui
+            .add_enabled(
+                can_create,
+                egui::Button::new(RichText::new("Ensemble anlegen").color(Color32::WHITE))
+                    .fill(GREEN),
+            )
+            .clicked()
+        {
+            // Reihenfolge wie in der Liste, damit ensembles.yaml stabil bleibt
+            let members = candidates
+                .iter()
+                .filter_map(|m| {
+                    self.ensemble_weights
+                        .get(&m.name)
+                        .map(|w| ensemble::EnsembleMember {
+                            model: m.name.clone(),
+                            weight: *w,
+                        })
+                })
+                .collect();
+
+            let ensemble = ensemble::Ensemble {
+                name: self.ensemble_name.trim().to_string(),
+                dataset_type: dataset,
+                members,
+                iou_threshold: self.ensemble_iou,
+            };
+
+            match ensemble.create() {
+                Ok(_) => {
+                    self.create_error(
+                        format!("Ensemble {} angelegt", ensemble.name),
+                        MessageType::Success,
+                    );
+                    self.ensemble_name.clear();
+                    self.ensemble_weights.clear();
+                    self.reload_models();
+                }
+                Err(e) => self.create_error(
+                    format!("Konnte Ensemble nicht anlegen: {:?}", e),
+                    MessageType::Error,
+                ),
+            }
+        }
+    }
+
+    fn model_metadata_ui(&mut self, ui: &mut egui::Ui) {
+        let Some(name) = self.selected_model.clone() else {
+            ui.label("Kein Modell gewählt");
+            return;
+        };
+
+        // Beim Modellwechsel die Daten neu laden
+        if self.metadata_edit.as_ref().map(|(n, _)| n) != Some(&name) {
+            match image_data_wrapper::ModelMetadata::load(&name) {
+                Ok(metadata) => {
+                    self.metadata_tags = metadata.tags.join(", ");
+                    self.metadata_edit = Some((name.clone(), metadata));
+                }
+                Err(e) => {
+                    ui.label(RichText::new(format!("model.json nicht lesbar: {:?}", e)).color(RED));
+                    return;
+                }
+            }
+        }
+
+        let Some((_, metadata)) = self.metadata_edit.as_mut() else {
+            return;
+        };
+
+        egui::Grid::new("model_metadata_grid")
+            .num_columns(2)
+            .show(ui, |ui| {
+                ui.label("Basis:");
+                ui.label(&metadata.base_model);
+                ui.end_row();
+
+                ui.label("Erstellt:");
+                ui.label(&metadata.created_at);
+                ui.end_row();
+
+                ui.label("Epochen insgesamt:");
+                ui.label(metadata.total_epochs.to_string());
+                ui.end_row();
+
+                ui.label("Abgeleitet von:");
+                ui.label(metadata.parent.clone().unwrap_or("-".to_string()));
+                ui.end_row();
+
+                ui.label("Dataset:");
+                ui.label(
+                    metadata
+                        .dataset_snapshot
+                        .as_ref()
+                        .map(|s| {
+                            format!(
+                                "{} train / {} val ({})",
+                                s.train_images, s.val_images, s.fingerprint
+                            )
+                        })
+                        .unwrap_or("-".to_string()),
+                );
+                ui.end_row();
+
+                ui.label("Tags (mit Komma):");
+                ui.text_edit_singleline(&mut self.metadata_tags);
+                ui.end_row();
+            });
+
+        ui.label("Notizen:");
+        ui.text_edit_multiline(&mut metadata.notes);
+
+        if ui.button("💾 Infos speichern").clicked() {
+            metadata.tags = self
+                .metadata_tags
+                .split(',')
+                .map(|t| t.trim().to_string())
+                .filter(|t| !t.is_empty())
+                .collect();
+
+            match metadata.save(&name) {
+                Ok(_) => {
+                    self.create_error("Model Infos gespeichert", MessageType::Success);
+                    self.reload_models();
+                }
+                Err(e) => self.create_error(
+                    format!("Konnte model.json nicht speichern: {:?}", e),
+                    MessageType::Error,
+                ),
+            }
+        }
     }
 
     fn update_buildings(&mut self) {
@@ -1503,6 +2618,60 @@ impl ScreenshotApp {
                 self.create_error("Buildings Bekommen", MessageType::Success);
             }
         }
+        if self.compare_native {
+            self.update_native_buildings();
+        }
+    }
+
+    fn update_native_buildings(&mut self) {
+        for is_build in [true, false] {
+            let (has_model, current) = if is_build {
+                (
+                    self.selected_build_model.is_some(),
+                    &self.current_native_build,
+                )
+            } else {
+                (
+                    self.selected_lvls_model.is_some(),
+                    &self.current_native_lvls,
+                )
+            };
+            if !has_model || current.is_some() {
+                continue;
+            }
+
+            let thread = if is_build {
+                &self.get_building_thread_build
+            } else {
+                &self.get_building_thread_lvls
+            };
+            let res = thread.poll_field::<Result<Vec<image_data_wrapper::Building>, FofError>>(
+                "native_buildings",
+            );
+
+            match res {
+                Some(Ok(bldngs)) => {
+                    if is_build {
+                        self.current_native_build = Some(bldngs);
+                    } else {
+                        self.current_native_lvls = Some(bldngs);
+                    }
+                }
+                Some(Err(FofError::ThreadNotInitialized)) | None => {}
+                Some(Err(e)) => {
+                    self.create_error(
+                        format!("Native Vorhersage fehlgeschlagen: {:?}", e),
+                        MessageType::Error,
+                    );
+                    // nicht jeden Frame neu melden
+                    if is_build {
+                        self.current_native_build = Some(vec![]);
+                    } else {
+                        self.current_native_lvls = Some(vec![]);
+                    }
+                }
+            }
+        }
     }
 
     pub fn recompute_buildings(&mut self) -> Vec<image_data_wrapper::Building> {
@@ -1516,9 +2685,9 @@ impl ScreenshotApp {
                 self.show_defences,
             );
 
-            // 2) apply confidence threshold
+            // 2) apply confidence thresholds (per class if set) and NMS
 
-            filtered.retain(|b| b.confidence >= self.min_confidence);
+            filtered = self.filter_detections(&filtered, self.selected_build_model.as_ref());
 
             // 3) optionally find hidden walls (append results)
             if self.find_hidden_walls_enabled {
@@ -1574,6 +2743,37 @@ impl ScreenshotApp {
         }
     }
 
+    /// Schwelle für eine Klasse: aus `class_thresholds.yaml` vom Modell, sonst `min_confidence`.
+    fn min_confidence_for(&self, model: Option<&String>, class_name: &str) -> f32 {
+        if !self.use_class_thresholds {
+            return self.min_confidence;
+        }
+
+        model
+            .and_then(|m| self.class_thresholds.get(m))
+            .map(|t| t.get(class_name, self.min_confidence))
+            .unwrap_or(self.min_confidence)
+    }
+
+    /// Confidence-Schwellen und NMS (Soft-NMS senkt Confidences, daher danach nochmal filtern).
+    fn filter_detections(
+        &self,
+        buildings: &[image_data_wrapper::Building],
+        model: Option<&String>,
+    ) -> Vec<image_data_wrapper::Building> {
+        let passes = |b: &image_data_wrapper::Building| {
+            b.confidence >= self.min_confidence_for(model, &b.class_name)
+        };
+
+        let filtered: Vec<_> = buildings.iter().filter(|b| passes(b)).cloned().collect();
+        let mut result = self
+            .nms_cache
+            .borrow_mut()
+            .nms(filtered, self.nms_mode, self.nms_iou);
+        result.retain(|b| passes(b));
+        result
+    }
+
     fn model_testen(&mut self, ui: &mut egui::Ui, ctx: &egui::Context) {
         ui.collapsing("Model Testen", |ui: &mut egui::Ui| {
             ui.group(|ui: &mut egui::Ui| {
@@ -1607,46 +2807,45 @@ impl ScreenshotApp {
                     .show_ui(ui, |ui| {
                         for model in self.current_models.clone() {
                             let score = model.rating;
-                            let name = model.name;
-
-                            let label = format!(
-                                "{name} ({score:.2}) Typ: {}",
-                                match model.dataset_type {
-                                    image_data_wrapper::DatasetType::Buildings =>
-                                        "🏗️ Building Model",
-                                    image_data_wrapper::DatasetType::Level => "🎯 Level Model",
-                                }
-                            );
-                            let dataset_type = image_data_wrapper::get_dataset_type(&name).unwrap();
+                            let name = model.name.clone();
+
+                            let label = model_label(&model);
+                            let Some(dataset_type) = model.dataset_type.clone() else {
+                                ui.add_enabled(false, egui::Label::new(label))
+                                    .on_disabled_hover_text(model_tooltip(&model));
+                                continue;
+                            };
 
                             if ui
-                                .selectable_label(
-                                    self.selected_lvls_model.as_deref() == Some(&name)
-                                        || self.selected_build_model.as_deref() == Some(&name),
-                                    RichText::new(label).color(
-                                        if dataset_type
-                                            == image_data_wrapper::DatasetType::Buildings
-                                        {
-                                            Color32::BROWN
-                                        } else if dataset_type
-                                            == image_data_wrapper::DatasetType::Level
-                                        {
-                                            Color32::PURPLE
-                                        } else {
-                                            unreachable!()
-                                        },
-                                    ),
+                                .add_enabled_ui(
+                                    model.is_usable()
+                                        && (dataset_type.is_buildings() || dataset_type.is_level()),
+                                    |ui| {
+                                        ui.selectable_label(
+                                            self.selected_lvls_model.as_deref() == Some(&name)
+                                                || self.selected_build_model.as_deref()
+                                                    == Some(&name),
+                                            RichText::new(label).color(
+                                                if dataset_type.is_buildings() {
+                                                    Color32::BROWN
+                                                } else {
+                                                    Color32::PURPLE
+                                                },
+                                            ),
+                                        )
+                                    },
                                 )
+                                .inner
+                                .on_hover_text(model_tooltip(&model))
                                 .clicked()
                             {
                                 if Some(name.clone()) == self.selected_build_model {
                                     self.selected_build_model = None;
                                 } else if Some(name.clone()) == self.selected_lvls_model {
                                     self.selected_lvls_model = None;
-                                } else if dataset_type == image_data_wrapper::DatasetType::Buildings
-                                {
+                                } else if dataset_type.is_buildings() {
                                     self.selected_build_model = Some(name);
-                                } else if dataset_type == image_data_wrapper::DatasetType::Level {
+                                } else if dataset_type.is_level() {
                                     self.selected_lvls_model = Some(name);
                                 }
                                 self.create_error("Model geändert", MessageType::Success);
@@ -1712,17 +2911,20 @@ impl ScreenshotApp {
                                 let mut buildings_to_draw = self.recompute_buildings();
 
                                 if let Some(level) = &self.current_buildings_lvls {
+                                    let mut level = self.filter_detections(
+                                        level,
+                                        self.selected_lvls_model.as_ref(),
+                                    );
+
                                     if self.combine_models_enabled {
                                         buildings_to_draw =
                                             filter_buildings::connect_level_and_buildings(
                                                 &buildings_to_draw,
-                                                level,
+                                                &level,
                                                 self.min_iou,
                                             )
                                     } else {
-                                        buildings_to_draw.append(&mut level.to_vec());
-                                        buildings_to_draw
-                                            .retain(|b| b.confidence >= self.min_confidence);
+                                        buildings_to_draw.append(&mut level);
                                     }
                                 }
                                 // Update wall connections each frame if enabled (we mutate self here)
@@ -1747,6 +2949,14 @@ impl ScreenshotApp {
                                     rect,
                                 );
 
+                                if self.compare_native {
+                                    self.draw_native_overlay(
+                                        &ui.painter_at(rect),
+                                        (img_w as f32, img_h as f32),
+                                        rect,
+                                    );
+                                }
+
                                 // Draw the avg_conf marker on your confidence slider elsewhere (see below)
                                 self.show_settings_in_window(ui);
                             }
@@ -1757,6 +2967,8 @@ impl ScreenshotApp {
             if self.in_test_mode != modeclone {
                 self.current_buildings_lvls = None;
                 self.current_buildings_build = None;
+                self.current_native_lvls = None;
+                self.current_native_build = None;
                 self.get_building_thread_lvls.set_field(
                     "buildings",
                     Err::<Vec<image_data_wrapper::Building>, FofError>(
@@ -1873,6 +3085,35 @@ impl ScreenshotApp {
 
                 ui.separator();
 
+                ui.label("NMS");
+                ui.horizontal(|ui| {
+                    for mode in filter_buildings::NmsMode::iter() {
+                        ui.selectable_value(&mut self.nms_mode, mode, mode.to_string());
+                    }
+                });
+                if self.nms_mode != filter_buildings::NmsMode::Off {
+                    ui.add_sized(
+                        vec2(300., 50.),
+                        egui::Slider::new(&mut self.nms_iou, 0.05..=0.95)
+                            .step_by(0.05)
+                            .text("NMS IOU"),
+                    );
+                }
+
+                ui.separator();
+
+                self.show_class_threshold_settings(ui);
+
+                ui.separator();
+
+                self.show_tiling_settings(ui);
+
+                ui.separator();
+
+                self.show_native_compare_settings(ui);
+
+                ui.separator();
+
                 ui.label("Label mode");
                 ui.horizontal(|ui| {
                     ui.selectable_value(&mut self.label_mode, LabelMode::ClassName, "Name");
@@ -2051,156 +3292,1461 @@ impl ScreenshotApp {
         }
     }
 
-    fn model_training(&mut self, ui: &mut egui::Ui) {
-        let mut idxes_to_remove = vec![];
-        for (idx, thrd) in self.train_threads.iter_mut().enumerate() {
-            if !thrd.is_running() {
-                idxes_to_remove.push(idx);
+    fn native_buildings_filtered(&self) -> Vec<image_data_wrapper::Building> {
+        // Gebäude und Level getrennt, sonst würde klassenübergreifendes NMS Level wegwerfen
+        let mut native = vec![];
+        for (list, model) in [
+            (&self.current_native_build, &self.selected_build_model),
+            (&self.current_native_lvls, &self.selected_lvls_model),
+        ] {
+            if let Some(list) = list {
+                native.extend(self.filter_detections(list, model.as_ref()));
             }
         }
+        native
+    }
 
-        for idx in idxes_to_remove {
-            let t = self.train_threads.remove(idx);
-            self.reload_models();
+    fn show_class_threshold_settings(&mut self, ui: &mut egui::Ui) {
+        ui.checkbox(&mut self.use_class_thresholds, "Schwellen pro Klasse")
+            .on_hover_text("Klassen ohne eigene Schwelle benutzen Min Conf");
+
+        self.poll_auto_tune();
+
+        if !self.use_class_thresholds {
+            return;
         }
-        ui.collapsing("Training", |ui: &mut egui::Ui| {
-            self.current_models.sort_by(|a, b| {
-                a.rating
-                    .partial_cmp(&b.rating)
-                    .unwrap_or(std::cmp::Ordering::Equal)
+
+        for model in [
+            self.selected_build_model.clone(),
+            self.selected_lvls_model.clone(),
+        ]
+        .into_iter()
+        .flatten()
+        {
+            ui.collapsing(format!("Schwellen {}", model), |ui| {
+                self.class_threshold_table(ui, &model);
+            });
+        }
+    }
+
+    fn class_threshold_table(&mut self, ui: &mut egui::Ui, model: &str) {
+        let names = self
+            .class_names_for_thresholds
+            .entry(model.to_string())
+            .or_insert_with(|| {
+                image_data_wrapper::get_dataset_type(model)
+                    .and_then(|d| image_data_wrapper::read_class_names(&d))
+                    .map(|names| names.into_iter().map(|(_, n)| n).collect())
+                    .unwrap_or_default()
+            })
+            .clone();
+        let thresholds = self
+            .class_thresholds
+            .entry(model.to_string())
+            .or_insert_with(|| class_thresholds::ClassThresholds::load(model));
+        let tuning = self.auto_tune_results.get(model);
+
+        egui::ScrollArea::vertical()
+            .max_height(250.0)
+            .id_salt(format!("class_thresholds_{}", model))
+            .show(ui, |ui| {
+                egui::Grid::new(format!("class_thresholds_grid_{}", model)).show(ui, |ui| {
+                    ui.label(RichText::new("Klasse").strong());
+                    ui.label(RichText::new("Eigene").strong());
+                    ui.label(RichText::new("Schwelle").strong());
+                    ui.label(RichText::new("F1 (Val)").strong());
+                    ui.end_row();
+
+                    for name in names.iter() {
+                        ui.label(name);
+
+                        let mut own = thresholds.thresholds.contains_key(name);
+                        if ui.checkbox(&mut own, "").changed() {
+                            if own {
+                                thresholds
+                                    .thresholds
+                                    .insert(name.clone(), self.min_confidence);
+                            } else {
+                                thresholds.thresholds.remove(name);
+                            }
+                        }
+
+                        match thresholds.thresholds.get_mut(name) {
+                            Some(value) => {
+                                ui.add(egui::Slider::new(value, 0.001..=1.0).step_by(0.01));
+                            }
+                            None => {
+                                ui.label(format!("{:.2} (global)", self.min_confidence));
+                            }
+                        }
+
+                        match tuning.and_then(|t| t.iter().find(|c| &c.class_name == name)) {
+                            Some(c) => ui.label(format!("{:.2}", c.f1)).on_hover_text(format!(
+                                "P {:.2} / R {:.2} bei {:.2}, {} Labels",
+                                c.precision, c.recall, c.threshold, c.ground_truth
+                            )),
+                            None => ui.label("-"),
+                        };
+                        ui.end_row();
+                    }
+                });
+            });
+
+        ui.horizontal(|ui| {
+            if ui.button("Speichern").clicked() {
+                let res = self
+                    .class_thresholds
+                    .get(model)
+                    .map(|t| t.save(model))
+                    .unwrap_or(Ok(()));
+                match res {
+                    Ok(_) => self.create_error("Schwellen gespeichert", MessageType::Success),
+                    Err(e) => self.create_error(
+                        format!("Konnte Schwellen nicht speichern: {:?}", e),
+                        MessageType::Error,
+                    ),
+                }
+            }
+
+            if ui.button("Neu laden").clicked() {
+                self.class_thresholds.insert(
+                    model.to_string(),
+                    class_thresholds::ClassThresholds::load(model),
+                );
+            }
+
+            match &self.auto_tune_job {
+                Some(job) if job.model_name == model => {
+                    ui.spinner();
+                    ui.label(format!("Auto-Tune {}/{}", job.done, job.total));
+                    if ui.button("Abbrechen").clicked() {
+                        job.cancel.store(true, Ordering::SeqCst);
+                    }
+                }
+                Some(_) => {
+                    ui.add_enabled(false, egui::Button::new("Auto-Tune (Val-Split)"))
+                        .on_disabled_hover_text("Es läuft schon ein Auto-Tune");
+                }
+                None => {
+                    if ui
+                        .button("Auto-Tune (Val-Split)")
+                        .on_hover_text(
+                            "Sagt den Val-Split vorher und nimmt pro Klasse die Schwelle mit dem besten F1",
+                        )
+                        .clicked()
+                    {
+                        self.auto_tune_job = Some(AutoTuneJob::start(
+                            model.to_string(),
+                            self.nms_mode,
+                            self.nms_iou,
+                        ));
+                    }
+                }
+            }
+        });
+    }
+
+    fn poll_auto_tune(&mut self) {
+        let Some(job) = self.auto_tune_job.as_mut() else {
+            return;
+        };
+        let Some(res) = job.poll() else {
+            return;
+        };
+
+        let model = job.model_name.clone();
+        self.auto_tune_job = None;
+
+        match res {
+            Ok(tuning) => {
+                let thresholds = self
+                    .class_thresholds
+                    .entry(model.clone())
+                    .or_insert_with(|| class_thresholds::ClassThresholds::load(&model));
+                for class in tuning.iter() {
+                    thresholds
+                        .thresholds
+                        .insert(class.class_name.clone(), class.threshold);
+                }
+
+                match thresholds.save(&model) {
+                    Ok(_) => self.create_error(
+                        format!("Auto-Tune fertig, {} Schwellen gespeichert", tuning.len()),
+                        MessageType::Success,
+                    ),
+                    Err(e) => self.create_error(
+                        format!("Auto-Tune fertig, aber nicht gespeichert: {:?}", e),
+                        MessageType::Error,
+                    ),
+                }
+                self.auto_tune_results.insert(model, tuning);
+            }
+            Err(e) => self.create_error(
+                format!("Auto-Tune fehlgeschlagen: {:?}", e),
+                MessageType::Error,
+            ),
+        }
+    }
+
+    fn show_tiling_settings(&mut self, ui: &mut egui::Ui) {
+        let mut changed = ui
+            .checkbox(&mut self.tiled_prediction, "In Kacheln vorhersagen")
+            .on_hover_text("Für kleine Objekte wie Mauern und Level auf großen Screenshots")
+            .changed();
+
+        if self.tiled_prediction {
+            let settings = &mut self.tile_settings;
+            for response in [
+                ui.add_sized(
+                    vec2(300., 50.),
+                    egui::Slider::new(&mut settings.tile_size, 256..=1280)
+                        .step_by(32.0)
+                        .text("Kachelgröße px"),
+                ),
+                ui.add_sized(
+                    vec2(300., 50.),
+                    egui::Slider::new(&mut settings.overlap, 0.0..=0.5)
+                        .step_by(0.05)
+                        .text("Überlappung"),
+                ),
+                ui.add_sized(
+                    vec2(300., 50.),
+                    egui::Slider::new(&mut settings.merge_iou, 0.05..=1.0)
+                        .step_by(0.05)
+                        .text("Merge IOU"),
+                ),
+                ui.add_sized(
+                    vec2(300., 50.),
+                    egui::Slider::new(&mut settings.min_confidence, 0.0..=1.0)
+                        .step_by(0.05)
+                        .text("Min Confidence vor dem Merge"),
+                ),
+            ] {
+                // Erst beim Loslassen, sonst wird bei jedem Frame neu vorhergesagt
+                changed |= response.drag_stopped() || (response.changed() && !response.dragged());
+            }
+        }
+
+        if changed {
+            if let Some(img) = self.selected_image.clone() {
+                self.start_getting_builds(img);
+            }
+        }
+    }
+
+    fn show_native_compare_settings(&mut self, ui: &mut egui::Ui) {
+        let before = self.compare_native;
+        ui.checkbox(&mut self.compare_native, "Nativ (ONNX) vergleichen");
+
+        if self.compare_native != before {
+            self.current_native_build = None;
+            self.current_native_lvls = None;
+            if self.compare_native {
+                if let Some(img) = self.selected_image.clone() {
+                    self.start_getting_builds(img);
+                }
+            }
+        }
+
+        // Auch ohne Häkchen abholen, der Export läuft ja weiter
+        if let Some(job) = &self.onnx_export_job {
+            match job.poll() {
+                None => {
+                    ui.horizontal(|ui| {
+                        ui.spinner();
+                        ui.label(format!("Exportiere {} nach ONNX...", job.model_name));
+                    });
+                }
+                Some(Ok(_)) => {
+                    let msg = format!("{} nach ONNX exportiert", job.model_name);
+                    self.onnx_export_job = None;
+                    self.create_error(msg, MessageType::Success);
+                }
+                Some(Err(e)) => {
+                    let msg = format!("ONNX Export von {} fehlgeschlagen: {:?}", job.model_name, e);
+                    self.onnx_export_job = None;
+                    self.create_error(msg, MessageType::Error);
+                }
+            }
+        }
+
+        // Mit dem nativen Backend braucht jede Vorhersage ein aktuelles best.onnx
+        let native_backend = inference_backend::backend().name() == native_inference::BACKEND_NAME;
+        if !self.compare_native && !native_backend {
+            return;
+        }
+
+        for model in [
+            self.selected_build_model.clone(),
+            self.selected_lvls_model.clone(),
+        ]
+        .into_iter()
+        .flatten()
+        {
+            if !native_inference::has_onnx_export(&model) {
+                ui.label(RichText::new(format!("{model}: kein aktuelles best.onnx")).color(YELLOW));
+                if ui
+                    .add_enabled(
+                        self.onnx_export_job.is_none(),
+                        egui::Button::new(format!("{model} nach ONNX exportieren")),
+                    )
+                    .on_disabled_hover_text("Es läuft schon ein Export")
+                    .clicked()
+                {
+                    self.onnx_export_job = Some(OnnxExportJob::start(model.clone()));
+                }
+            }
+        }
+
+        if !self.compare_native {
+            return;
+        }
+
+        let mut python = self.recompute_buildings();
+        if let Some(level) = &self.current_buildings_lvls {
+            python.extend(self.filter_detections(level, self.selected_lvls_model.as_ref()));
+        }
+        let native = self.native_buildings_filtered();
+
+        ui.label(format!(
+            "Python: {} Boxen (⌀ {:.2})",
+            python.len(),
+            image_data_wrapper::get_avg_confidence(&python)
+        ));
+        ui.label(
+            RichText::new(format!(
+                "Nativ: {} Boxen (⌀ {:.2})",
+                native.len(),
+                image_data_wrapper::get_avg_confidence(&native)
+            ))
+            .color(YELLOW),
+        );
+    }
+
+    /// Native Boxen gestrichelt in Gelb über die Python Boxen zeichnen.
+    fn draw_native_overlay(
+        &self,
+        painter: &egui::Painter,
+        image_px_size: (f32, f32),
+        image_rect: egui::Rect,
+    ) {
+        let scale_x = image_rect.width() / image_px_size.0;
+        let scale_y = image_rect.height() / image_px_size.1;
+
+        for b in self.native_buildings_filtered() {
+            let (x1, y1, x2, y2) = b.bounding_box;
+            let p1 = Pos2::new(
+                image_rect.left() + x1 * scale_x,
+                image_rect.top() + y1 * scale_y,
+            );
+            let p2 = Pos2::new(
+                image_rect.left() + x2 * scale_x,
+                image_rect.top() + y2 * scale_y,
+            );
+            let r = egui::Rect::from_two_pos(p1, p2);
+
+            let stroke = egui::Stroke::new(1.5, YELLOW);
+            for (a, b) in [
+                (r.left_top(), r.right_top()),
+                (r.right_top(), r.right_bottom()),
+                (r.right_bottom(), r.left_bottom()),
+                (r.left_bottom(), r.left_top()),
+            ] {
+                painter.extend(egui::Shape::dashed_line(&[a, b], stroke, 6.0, 4.0));
+            }
+        }
+    }
+
+    fn on_training_done(&mut self, model_name: &str) {
+        native_inference::forget_model(model_name);
+        self.metadata_edit = None;
+        // laufender Inference Server soll die neuen Gewichte benutzen
+        inference_server::reload_model(model_name);
+        self.reload_models();
+    }
+
+    fn training_queue_ui(&mut self, ui: &mut egui::Ui) {
+        ui.label(RichText::new("Warteschlange").strong());
+
+        if self.training_queue.jobs.is_empty() {
+            ui.label("Keine Trainings in der Warteschlange");
+            return;
+        }
+
+        let mut to_remove = None;
+        let mut stop: Option<bool> = None; // Some(true) = sofort abbrechen
+
+        egui::Grid::new("training_queue_grid")
+            .striped(true)
+            .show(ui, |ui| {
+                ui.label("Modell");
+                ui.label("Epochen");
+                ui.label("Status");
+                ui.label("PID");
+                ui.label("Eingereiht");
+                ui.label("Gestartet");
+                ui.label("Beendet");
+                ui.label("");
+                ui.end_row();
+
+                let short_time = |t: &Option<String>| {
+                    t.as_ref()
+                        .and_then(|t| chrono::DateTime::parse_from_rfc3339(t).ok())
+                        .map(|t| t.format("%d.%m. %H:%M").to_string())
+                        .unwrap_or("-".to_string())
+                };
+
+                for job in self.training_queue.jobs.iter() {
+                    ui.label(&job.model_name);
+                    ui.label(job.epochen.to_string());
+                    match &job.state {
+                        training_queue::JobState::Queued => ui.label("⏳ Wartet"),
+                        training_queue::JobState::Running => {
+                            ui.label(RichText::new("🏃 Läuft").color(YELLOW))
+                        }
+                        training_queue::JobState::Finished => {
+                            ui.label(RichText::new("✔ Fertig").color(GREEN))
+                        }
+                        training_queue::JobState::Stopped(epoch) => ui
+                            .label(RichText::new("⏹ Gestoppt").color(YELLOW))
+                            .on_hover_text(format!(
+                                "Gestoppt nach Epoche {}/{}",
+                                epoch, job.epochen
+                            )),
+                        training_queue::JobState::Failed(reason) => ui
+                            .label(RichText::new("✖ Fehlgeschlagen").color(RED))
+                            .on_hover_text(reason),
+                    };
+                    ui.label(job.pid.map(|p| p.to_string()).unwrap_or("-".to_string()));
+                    ui.label(short_time(&Some(job.queued_at.clone())));
+                    ui.label(short_time(&job.started_at));
+                    ui.label(short_time(&job.finished_at));
+
+                    if job.state == training_queue::JobState::Running {
+                        ui.horizontal(|ui| {
+                            if ui
+                                .add_enabled(!job.stop_requested, egui::Button::new("Stop"))
+                                .on_hover_text("Nach dieser Epoche stoppen, best.pt und last.pt bleiben gültig")
+                                .clicked()
+                            {
+                                stop = Some(false);
+                            }
+                            if ui
+                                .add(
+                                    egui::Button::new(RichText::new("Abbrechen").color(Color32::WHITE))
+                                        .fill(RED),
+                                )
+                                .on_hover_text("Jetzt abbrechen (SIGINT), nach 30s wird hart beendet")
+                                .clicked()
+                            {
+                                stop = Some(true);
+                            }
+                        });
+                    } else if ui.button("🗑").clicked() {
+                        to_remove = Some(job.id.clone());
+                    }
+                    ui.end_row();
+                }
+            });
+
+        if let Some(now) = stop {
+            let res = if now {
+                self.training_queue.interrupt_running()
+            } else {
+                self.training_queue.request_stop()
+            };
+            match res {
+                Ok(_) if now => {
+                    self.create_error("Training wird abgebrochen", MessageType::Success)
+                }
+                Ok(_) => self.create_error(
+                    "Training stoppt nach der aktuellen Epoche",
+                    MessageType::Success,
+                ),
+                Err(e) if now => self.create_error(
+                    format!(
+                        "SIGINT kam nicht an ({:?}), Training wird nach {}s hart beendet",
+                        e,
+                        training_queue::INTERRUPT_TIMEOUT.as_secs()
+                    ),
+                    MessageType::Warning,
+                ),
+                Err(e) => self.create_error(
+                    format!("Training konnte nicht gestoppt werden: {:?}", e),
+                    MessageType::Error,
+                ),
+            }
+        }
+
+        if let Some(id) = to_remove {
+            if let Err(e) = self.training_queue.remove(&id) {
+                self.create_error(format!("{:?}", e), MessageType::Error);
+            }
+        }
+
+        if ui.button("Fertige Jobs entfernen").clicked() {
+            if let Err(e) = self.training_queue.clear_done() {
+                self.create_error(format!("{:?}", e), MessageType::Error);
+            }
+        }
+    }
+
+    fn training_config_ui(&mut self, ui: &mut egui::Ui, model_name: &str) {
+        ui.horizontal(|ui| {
+            egui::ComboBox::from_label("Preset")
+                .selected_text("Preset laden")
+                .show_ui(ui, |ui| {
+                    for (name, config) in self.training_presets.clone() {
+                        if ui.selectable_label(false, &name).clicked() {
+                            self.training_config = config;
+                            self.create_error(
+                                format!("Preset {} geladen", name),
+                                MessageType::Success,
+                            );
+                        }
+                    }
+                });
+
+            if ui.button("Config vom Modell laden").clicked() {
+                match image_data_wrapper::TrainingConfig::load_for_model(model_name) {
+                    Ok(config) => {
+                        self.training_config = config;
+                        self.create_error("Config geladen", MessageType::Success);
+                    }
+                    Err(e) => self.create_error(
+                        format!("Modell hat noch keine Config: {:?}", e),
+                        MessageType::Warning,
+                    ),
+                }
+            }
+        });
+
+        ui.horizontal(|ui| {
+            ui.text_edit_singleline(&mut self.new_preset_name);
+            if ui.button("Als Preset speichern").clicked() {
+                let name = self.new_preset_name.trim().to_string();
+                if name.is_empty() {
+                    self.create_error("Preset braucht einen Namen", MessageType::Warning);
+                } else {
+                    match image_data_wrapper::TrainingConfig::save_preset(
+                        &name,
+                        &self.training_config,
+                    ) {
+                        Ok(_) => {
+                            self.training_presets =
+                                image_data_wrapper::TrainingConfig::load_presets();
+                            self.new_preset_name.clear();
+                            self.create_error("Preset gespeichert", MessageType::Success);
+                        }
+                        Err(e) => self.create_error(
+                            format!("Preset konnte nicht gespeichert werden: {:?}", e),
+                            MessageType::Error,
+                        ),
+                    }
+                }
+            }
+        });
+
+        let config = &mut self.training_config;
+
+        egui::Grid::new("training_config_grid")
+            .num_columns(2)
+            .show(ui, |ui| {
+                ui.label("Gerät");
+                egui::ComboBox::from_id_source("training_device")
+                    .selected_text(config.device.to_string())
+                    .show_ui(ui, |ui| {
+                        for device in image_data_wrapper::TrainingDevice::iter() {
+                            ui.selectable_value(&mut config.device, device, device.to_string());
+                        }
+                    });
+                ui.end_row();
+
+                ui.label("Bildgröße");
+                ui.add(
+                    egui::DragValue::new(&mut config.imgsz)
+                        .range(32..=1920)
+                        .speed(32),
+                );
+                ui.end_row();
+
+                ui.label("Batch");
+                ui.add(egui::DragValue::new(&mut config.batch).range(1..=128));
+                ui.end_row();
+
+                ui.label("Workers");
+                ui.add(egui::DragValue::new(&mut config.workers).range(0..=32));
+                ui.end_row();
+
+                ui.label("Optimizer");
+                egui::ComboBox::from_id_source("training_optimizer")
+                    .selected_text(config.optimizer.clone())
+                    .show_ui(ui, |ui| {
+                        for opt in ["SGD", "Adam", "AdamW", "auto"] {
+                            ui.selectable_value(&mut config.optimizer, opt.to_string(), opt);
+                        }
+                    });
+                ui.end_row();
+
+                ui.label("lr0");
+                ui.add(
+                    egui::DragValue::new(&mut config.lr0)
+                        .speed(0.001)
+                        .range(0.0..=1.0),
+                );
+                ui.end_row();
+
+                ui.label("lrf");
+                ui.add(
+                    egui::DragValue::new(&mut config.lrf)
+                        .speed(0.001)
+                        .range(0.0..=1.0),
+                );
+                ui.end_row();
+
+                ui.label("Momentum");
+                ui.add(
+                    egui::DragValue::new(&mut config.momentum)
+                        .speed(0.001)
+                        .range(0.0..=1.0),
+                );
+                ui.end_row();
+
+                ui.label("Weight Decay");
+                ui.add(
+                    egui::DragValue::new(&mut config.weight_decay)
+                        .speed(0.0001)
+                        .range(0.0..=0.1),
+                );
+                ui.end_row();
+
+                ui.label("Patience");
+                ui.horizontal(|ui| {
+                    let mut auto = config.patience.is_none();
+                    if ui.checkbox(&mut auto, "30% der Epochen").changed() {
+                        config.patience = if auto { None } else { Some(50) };
+                    }
+                    if let Some(patience) = config.patience.as_mut() {
+                        ui.add(egui::DragValue::new(patience).range(0..=1000));
+                    }
+                });
+                ui.end_row();
+
+                ui.label("Warmup Epochen");
+                ui.add(
+                    egui::DragValue::new(&mut config.warmup_epochs)
+                        .speed(0.1)
+                        .range(0.0..=20.0),
+                );
+                ui.end_row();
+
+                ui.label("Cache");
+                egui::ComboBox::from_id_source("training_cache")
+                    .selected_text(config.cache.to_string())
+                    .show_ui(ui, |ui| {
+                        for cache in image_data_wrapper::CacheMode::iter() {
+                            ui.selectable_value(&mut config.cache, cache, cache.to_string());
+                        }
+                    });
+                ui.end_row();
+
+                ui.label("AMP / Cosine LR");
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut config.amp, "AMP");
+                    ui.checkbox(&mut config.cos_lr, "Cosine LR");
+                });
+                ui.end_row();
+            });
+
+        ui.collapsing("Augmentation", |ui| {
+            egui::Grid::new("training_augmentation_grid")
+                .num_columns(2)
+                .show(ui, |ui| {
+                    for (label, value) in [
+                        ("hsv_h", &mut config.hsv_h),
+                        ("hsv_s", &mut config.hsv_s),
+                        ("hsv_v", &mut config.hsv_v),
+                        ("translate", &mut config.translate),
+                        ("scale", &mut config.scale),
+                        ("flipud", &mut config.flipud),
+                        ("fliplr", &mut config.fliplr),
+                        ("mosaic", &mut config.mosaic),
+                        ("mixup", &mut config.mixup),
+                        ("copy_paste", &mut config.copy_paste),
+                    ] {
+                        ui.label(label);
+                        ui.add(Slider::new(value, 0.0..=1.0));
+                        ui.end_row();
+                    }
+
+                    ui.label("degrees");
+                    ui.add(Slider::new(&mut config.degrees, 0.0..=180.0));
+                    ui.end_row();
+
+                    ui.label("shear");
+                    ui.add(Slider::new(&mut config.shear, 0.0..=45.0));
+                    ui.end_row();
+
+                    ui.label("perspective");
+                    ui.add(Slider::new(&mut config.perspective, 0.0..=0.001));
+                    ui.end_row();
+                });
+        });
+
+        if config.device == image_data_wrapper::TrainingDevice::Cpu && config.amp {
+            ui.label(RichText::new("AMP wird auf der CPU von ultralytics ignoriert").color(YELLOW));
+        }
+    }
+
+    fn model_training(&mut self, ui: &mut egui::Ui) {
+        ui.collapsing("Training", |ui: &mut egui::Ui| {
+            self.current_models.sort_by(|a, b| {
+                a.rating
+                    .partial_cmp(&b.rating)
+                    .unwrap_or(std::cmp::Ordering::Equal)
+            });
+
+            egui::ComboBox::from_label("Modell auswählen")
+                .selected_text(
+                    self.selected_model
+                        .clone()
+                        .unwrap_or_else(|| "Kein Modell gewählt".into()),
+                )
+                .show_ui(ui, |ui| {
+                    for model in self.current_models.clone() {
+                        let score = model.rating;
+                        let name = model.name.clone();
+
+                        let is_training = self.training_queue.is_training(&name);
+                        let is_queued = self.training_queue.is_queued(&name);
+
+                        let mut label = model_label(&model);
+
+                        // model.status ist nur so aktuell wie das letzte reload_models
+                        if is_training && model.status != image_data_wrapper::ModelStatus::Training
+                        {
+                            label.push_str(" (Trainiert gerade)");
+                        } else if is_queued {
+                            label.push_str(" (In Warteschlange)");
+                        }
+
+                        if ui
+                            .add_enabled_ui(model.can_train(), |ui| {
+                                ui.selectable_label(
+                                    self.selected_model.as_deref() == Some(name.as_str()),
+                                    RichText::new(label).color(if is_training {
+                                        YELLOW
+                                    } else {
+                                        Color32::GRAY
+                                    }),
+                                )
+                            })
+                            .inner
+                            .on_hover_text(model_tooltip(&model))
+                            .clicked()
+                        {
+                            self.selected_model = Some(name.clone());
+                            self.create_error("Model geändert", MessageType::Success);
+                        }
+                    }
+                });
+
+            if let Some(progress) = self.training_queue.progress.as_ref() {
+                if self.selected_model.as_deref() == Some(progress.model_name.as_str()) {
+                    show_training_progress(ui, progress);
+                }
+            }
+
+            if let Some(selected) = self.selected_model.clone() {
+                let weights = image_data_wrapper::WeightsStatus::of(&selected);
+                ui.horizontal(|ui| {
+                    let file_status =
+                        |ui: &mut egui::Ui, name: &str, time: Option<std::time::SystemTime>| {
+                            match time {
+                                Some(t) => ui
+                                    .label(RichText::new(format!("{} ✔", name)).color(GREEN))
+                                    .on_hover_text(
+                                        chrono::DateTime::<Local>::from(t)
+                                            .format("%d.%m. %H:%M")
+                                            .to_string(),
+                                    ),
+                                None => ui.label(
+                                    RichText::new(format!("{} ✖", name)).color(Color32::GRAY),
+                                ),
+                            };
+                        };
+                    file_status(ui, "best.pt", weights.best);
+                    file_status(ui, "last.pt", weights.last);
+                    file_status(ui, "resume.pt", weights.resume);
+                });
+
+                let interrupted = self
+                    .current_models
+                    .iter()
+                    .find(|m| m.name == selected)
+                    .and_then(|m| m.metadata.interrupted.clone());
+                if let Some(interrupted) = interrupted {
+                    let busy = self.training_queue.is_training(&selected)
+                        || self.training_queue.is_queued(&selected);
+                    ui.horizontal(|ui| {
+                        ui.label(format!(
+                            "Abgebrochen nach Epoche {}/{}",
+                            interrupted.epochs_done, interrupted.epochen
+                        ));
+                        if ui
+                            .add_enabled(
+                                !busy && weights.can_resume(),
+                                egui::Button::new("Training fortsetzen"),
+                            )
+                            .clicked()
+                        {
+                            match self.training_queue.enqueue_resume(&selected) {
+                                Ok(_) => self.create_error(
+                                    "Fortsetzen zur Warteschlange hinzugefügt",
+                                    MessageType::Success,
+                                ),
+                                Err(e) => self.create_error(
+                                    format!("Konnte Training nicht fortsetzen: {:?}", e),
+                                    MessageType::Error,
+                                ),
+                            }
+                        }
+                    });
+                }
+
+                ui.horizontal(|ui| {
+                    ui.label("epochen:");
+                    ui.text_edit_singleline(&mut self.current_epochen);
+                });
+
+                let res = self.current_epochen.trim().parse::<usize>();
+
+                ui.collapsing("Hyperparameter", |ui| {
+                    self.training_config_ui(ui, &selected);
+                });
+
+                let text = if self.training_queue.running_job().is_some() {
+                    "Zur Warteschlange hinzufügen"
+                } else {
+                    "Start Training"
+                };
+                if ui
+                    .add(
+                        egui::Button::new(RichText::new(text).color(Color32::WHITE)).fill(GREEN), // Grün
+                    )
+                    .clicked()
+                {
+                    match res {
+                        Err(e) => self.create_error(
+                            format!("Falsche angabe der epochen: {:?}", e),
+                            MessageType::Error,
+                        ),
+                        Ok(epochen) => match self.training_queue.enqueue(
+                            &selected,
+                            epochen,
+                            self.training_config.clone(),
+                        ) {
+                            Ok(_) => self.create_error(
+                                "Training zur Warteschlange hinzugefügt",
+                                MessageType::Success,
+                            ),
+                            Err(e) => self.create_error(
+                                format!("Konnte Training nicht einreihen: {:?}", e),
+                                MessageType::Error,
+                            ),
+                        },
+                    }
+                }
+            }
+
+            ui.separator();
+            self.training_queue_ui(ui);
+        });
+    }
+
+    fn model(&mut self, ui: &mut egui::Ui, ctx: &egui::Context) {
+        ui.separator();
+        self.manage_models(ui);
+        ui.separator();
+        self.model_training(ui);
+        ui.separator();
+        self.model_testen(ui, ctx);
+        ui.separator();
+        ui.collapsing("Hyperparameter Sweep", |ui| {
+            self.sweep_ui(ui);
+        });
+        ui.separator();
+        ui.collapsing("TestVals", |ui| {
+            self.show_selectable_models(ui, true);
+            if let Some(sm) = self.selected_model.clone() {
+                ui.horizontal(|ui| {
+                    if ui.button("Generate TestVals").clicked() {
+                        self.create_error("Gereating TestVals...", MessageType::Success);
+                        let res = image_data_wrapper::get_testvals(sm.clone());
+                        if let Ok(_) = res {
+                            self.create_error("Gereated TestVals", MessageType::Success);
+                        } else if let Err(fe) = &res {
+                            self.create_error(
+                                format!("failed getting testvals: {:?}", fe),
+                                MessageType::Error,
+                            );
+                        }
+                        self.class_metrics = Some((sm.clone(), res));
+                    }
+
+                    if ui
+                        .button("Letzte Ergebnisse laden")
+                        .on_hover_text(image_data_wrapper::class_metrics_path(&sm))
+                        .clicked()
+                    {
+                        self.class_metrics =
+                            Some((sm.clone(), image_data_wrapper::read_class_metrics(&sm)));
+                    }
+                });
+            }
+
+            self.class_metrics_ui(ui);
+        });
+        ui.separator();
+        ui.collapsing("Logs", |ui| {
+            self.log_viewer_ui(ui);
+        });
+    }
+
+    fn log_viewer_ui(&mut self, ui: &mut egui::Ui) {
+        self.show_selectable_models(ui, false);
+        let Some(model_name) = self.selected_model.clone() else {
+            ui.label("Kein Modell gewählt");
+            return;
+        };
+
+        // Liste und Inhalt nur alle `LOG_REFRESH` neu lesen, nicht jeden Frame
+        let due = self
+            .log_refreshed
+            .map(|t| t.elapsed() >= LOG_REFRESH)
+            .unwrap_or(true);
+        if due {
+            self.log_refreshed = Some(std::time::Instant::now());
+        }
+        if due
+            || !self
+                .log_list
+                .as_ref()
+                .is_some_and(|(m, _)| *m == model_name)
+        {
+            self.log_list = Some((model_name.clone(), run_log::list_logs(&model_name)));
+        }
+        let logs = self
+            .log_list
+            .as_ref()
+            .map(|(_, l)| l.clone())
+            .unwrap_or_default();
+
+        if logs.is_empty() {
+            ui.label(format!(
+                "Keine Logs in {}",
+                run_log::log_dir(&model_name).display()
+            ));
+            return;
+        }
+
+        // Anderes Modell gewählt oder Log gelöscht: das neueste nehmen
+        if !self
+            .selected_log
+            .as_ref()
+            .is_some_and(|p| logs.iter().any(|l| &l.path == p))
+        {
+            self.selected_log = Some(logs[0].path.clone());
+        }
+        let selected = self.selected_log.clone().unwrap();
+
+        ui.horizontal(|ui| {
+            egui::ComboBox::from_label("Log")
+                .selected_text(
+                    selected
+                        .file_name()
+                        .map(|n| n.to_string_lossy().to_string())
+                        .unwrap_or_default(),
+                )
+                .show_ui(ui, |ui| {
+                    for log in logs.iter() {
+                        if ui
+                            .selectable_label(log.path == selected, log.file_name())
+                            .on_hover_text(format!("{} KB", log.size / 1024))
+                            .clicked()
+                        {
+                            self.selected_log = Some(log.path.clone());
+                        }
+                    }
+                });
+
+            if ui.button("Neuestes").clicked() {
+                self.selected_log = Some(logs[0].path.clone());
+            }
+        });
+
+        ui.horizontal(|ui| {
+            ui.label("Suche:");
+            ui.text_edit_singleline(&mut self.log_search);
+            ui.checkbox(&mut self.log_auto_scroll, "Auto-Scroll");
+        });
+
+        // Beim laufenden Training wird nur das Neue dazugelesen
+        let selected = self.selected_log.clone().unwrap();
+        match self.log_reader.as_mut() {
+            Some(reader) if reader.path == selected => {
+                if due {
+                    reader.update();
+                }
+            }
+            _ => {
+                let mut reader = run_log::LogReader::new(selected);
+                reader.update();
+                self.log_reader = Some(reader);
+            }
+        }
+
+        let Some(lines) = self.log_reader.as_ref().map(|r| &r.lines) else {
+            return;
+        };
+
+        let search = self.log_search.to_lowercase();
+        let shown: Vec<&String> = lines
+            .iter()
+            .filter(|l| search.is_empty() || l.to_lowercase().contains(&search))
+            .collect();
+
+        if !search.is_empty() {
+            ui.label(format!("{} von {} Zeilen", shown.len(), lines.len()));
+        }
+
+        let row_height = ui.text_style_height(&egui::TextStyle::Monospace);
+        egui::ScrollArea::both()
+            .max_height(400.0)
+            .auto_shrink([false, true])
+            .stick_to_bottom(self.log_auto_scroll)
+            .show_rows(ui, row_height, shown.len(), |ui, range| {
+                for line in shown[range].iter() {
+                    ui.monospace(line.as_str());
+                }
+            });
+    }
+
+    fn sweep_ui(&mut self, ui: &mut egui::Ui) {
+        ui.label(RichText::new("Neuer Sweep").strong());
+        ui.label(
+            "Jeder Trial ist ein Fork vom gewählten Modell und wird mit der Config aus \
+             \"Hyperparameter\" (Training) plus den Werten unten trainiert.",
+        );
+
+        self.show_selectable_models(ui, true);
+
+        ui.horizontal(|ui| {
+            ui.label("Name:");
+            ui.text_edit_singleline(&mut self.sweep_name);
+            ui.label("Epochen pro Trial:");
+            ui.add(egui::DragValue::new(&mut self.sweep_epochen).range(1..=10000));
+        });
+
+        let mut params = vec![];
+        let mut parse_error = None;
+
+        egui::Grid::new("sweep_params_grid").show(ui, |ui| {
+            for param in sweep::SweepParam::iter() {
+                let mut enabled = self.sweep_enabled.contains(&param);
+                if ui.checkbox(&mut enabled, param.to_string()).changed() {
+                    if enabled {
+                        self.sweep_enabled.insert(param);
+                    } else {
+                        self.sweep_enabled.remove(&param);
+                    }
+                }
+
+                let text = self.sweep_values.entry(param).or_default();
+                ui.add_enabled(
+                    enabled,
+                    egui::TextEdit::singleline(text)
+                        .hint_text(format!("{}", param.get(&self.training_config))),
+                );
+
+                if enabled {
+                    match sweep::parse_values(text) {
+                        Ok(values) if !values.is_empty() => params.push((param, values)),
+                        Ok(_) => parse_error = Some(format!("{}: keine Werte", param.to_string())),
+                        Err(e) => parse_error = Some(format!("{}: {:?}", param.to_string(), e)),
+                    }
+                }
+                ui.end_row();
+            }
+        });
+
+        ui.horizontal(|ui| {
+            let mut random = self.sweep_random.is_some();
+            ui.radio_value(&mut random, false, "Grid (alle Kombinationen)");
+            ui.radio_value(&mut random, true, "Zufällig");
+
+            if random {
+                let trials = self.sweep_random.get_or_insert(8);
+                ui.add(egui::DragValue::new(trials).range(1..=1000));
+                ui.label("Trials");
+            } else {
+                self.sweep_random = None;
+            }
+        });
+
+        let space = sweep::SweepSpace {
+            base: self.training_config.clone(),
+            params,
+            mode: match self.sweep_random {
+                Some(trials) => sweep::SweepMode::Random(trials),
+                None => sweep::SweepMode::Grid,
+            },
+        };
+
+        if let Some(e) = &parse_error {
+            ui.label(RichText::new(e).color(RED));
+        } else {
+            ui.label(format!(
+                "{} Trials à {} Epochen",
+                space.trial_count(),
+                self.sweep_epochen
+            ));
+        }
+
+        if let Some(parent) = self.selected_model.clone() {
+            let name = self.sweep_name.trim().to_string();
+            if parse_error.is_none()
+                && !name.is_empty()
+                && ui
+                    .add(
+                        egui::Button::new(RichText::new("Sweep starten").color(Color32::WHITE))
+                            .fill(GREEN),
+                    )
+                    .clicked()
+            {
+                match sweep::Sweep::start(
+                    &name,
+                    &parent,
+                    self.sweep_epochen,
+                    &space,
+                    &mut self.training_queue,
+                ) {
+                    Ok(sweep) => {
+                        self.create_error(
+                            format!("{} Trials eingereiht", sweep.trials.len()),
+                            MessageType::Success,
+                        );
+                        self.selected_sweep = Some(sweep.name.clone());
+                        self.sweeps = sweep::Sweep::load_all();
+                        self.sweep_name.clear();
+                        self.reload_models();
+                    }
+                    Err(e) => self.create_error(
+                        format!("Sweep konnte nicht gestartet werden: {:?}", e),
+                        MessageType::Error,
+                    ),
+                }
+            }
+        }
+
+        ui.separator();
+        self.sweep_results_ui(ui);
+    }
+
+    fn sweep_results_ui(&mut self, ui: &mut egui::Ui) {
+        ui.label(RichText::new("Ergebnisse").strong());
+
+        if self.sweeps.is_empty() {
+            ui.label("Noch keine Sweeps");
+            return;
+        }
+
+        egui::ComboBox::from_label("Sweep")
+            .selected_text(self.selected_sweep.clone().unwrap_or("-".to_string()))
+            .show_ui(ui, |ui| {
+                for sweep in self.sweeps.iter() {
+                    ui.selectable_value(
+                        &mut self.selected_sweep,
+                        Some(sweep.name.clone()),
+                        format!(
+                            "{} ({} Trials, von {})",
+                            sweep.name,
+                            sweep.trials.len(),
+                            sweep.parent_model
+                        ),
+                    );
+                }
             });
 
-            egui::ComboBox::from_label("Modell auswählen")
-                .selected_text(
-                    self.selected_model
-                        .clone()
-                        .unwrap_or_else(|| "Kein Modell gewählt".into()),
-                )
-                .show_ui(ui, |ui| {
-                    for model in self.current_models.clone() {
-                        let score = model.rating;
-                        let name = model.name.clone();
+        let Some(sweep) = self
+            .selected_sweep
+            .as_ref()
+            .and_then(|name| self.sweeps.iter().find(|s| &s.name == name))
+            .cloned()
+        else {
+            return;
+        };
 
-                        let mut is_training = false;
-                        let mut epochs = None;
+        // results.csv nur alle paar Sekunden neu lesen
+        let stale = match &self.sweep_results {
+            Some((name, at, _)) => name != &sweep.name || at.elapsed().as_secs() >= 2,
+            None => true,
+        };
+        if stale {
+            let results = sweep.results(&self.training_queue, &self.rating_formula);
+            self.sweep_results = Some((sweep.name.clone(), std::time::Instant::now(), results));
+        }
+        let Some((_, _, results)) = &self.sweep_results else {
+            return;
+        };
+        let results = results.clone();
+        let best = sweep::Sweep::best_trial(&results);
+
+        egui::ScrollArea::horizontal().show(ui, |ui| {
+            egui::Grid::new("sweep_results_grid")
+                .striped(true)
+                .show(ui, |ui| {
+                    ui.label("Trial");
+                    for (param, _) in sweep.trials[0].values.iter() {
+                        ui.label(param.to_string());
+                    }
+                    for title in ["Status", "Epochen", "P", "R", "mAP50", "mAP50-95", "Score"] {
+                        ui.label(title);
+                    }
+                    ui.end_row();
 
-                        let m = Some(model.name);
+                    for (i, (trial, result)) in sweep.trials.iter().zip(results.iter()).enumerate()
+                    {
+                        let color = if Some(i) == best {
+                            GREEN
+                        } else {
+                            ui.visuals().text_color()
+                        };
+                        let cell = |ui: &mut egui::Ui, text: String| {
+                            ui.label(RichText::new(text).color(color));
+                        };
 
-                        for (idx, thrd) in self.train_threads.iter_mut().enumerate() {
-                            if m == thrd.model_name {
-                                is_training = thrd.is_running();
-                                epochs = thrd.epochen;
-                                break;
-                            }
+                        cell(ui, trial.model_name.clone());
+                        for (_, value) in trial.values.iter() {
+                            cell(ui, format!("{}", value));
                         }
-
-                        let mut label = format!(
-                            "{name} ({score:.2}) Typ: {}",
-                            match model.dataset_type {
-                                image_data_wrapper::DatasetType::Buildings => "🏗️ Building Model",
-                                image_data_wrapper::DatasetType::Level => "🎯 Level Model",
+                        match &result.state {
+                            Some(training_queue::JobState::Queued) => {
+                                cell(ui, "⏳ Wartet".to_string())
+                            }
+                            Some(training_queue::JobState::Running) => {
+                                cell(ui, "🏃 Läuft".to_string())
+                            }
+                            Some(training_queue::JobState::Finished) => {
+                                cell(ui, "✔ Fertig".to_string())
+                            }
+                            Some(training_queue::JobState::Stopped(epoch)) => {
+                                cell(ui, format!("⏹ Gestoppt nach Epoche {}", epoch))
                             }
+                            Some(training_queue::JobState::Failed(reason)) => {
+                                ui.label(RichText::new("✖ Fehlgeschlagen").color(RED))
+                                    .on_hover_text(reason);
+                            }
+                            None => cell(ui, "-".to_string()),
+                        }
+                        cell(ui, format!("{}/{}", result.epochs, sweep.epochen));
+
+                        let metric = |f: fn(&image_data_wrapper::EpochMetrics) -> f64| {
+                            result
+                                .best
+                                .as_ref()
+                                .map(|m| format!("{:.3}", f(m)))
+                                .unwrap_or("-".to_string())
+                        };
+                        cell(ui, metric(|m| m.precision));
+                        cell(ui, metric(|m| m.recall));
+                        cell(ui, metric(|m| m.map_50));
+                        cell(ui, metric(|m| m.map_50_95));
+                        cell(
+                            ui,
+                            result
+                                .score
+                                .map(|s| format!("{:.3}", s))
+                                .unwrap_or("-".to_string()),
                         );
+                        ui.end_row();
+                    }
+                });
+        });
 
-                        if let Some(eps) = epochs {
-                            label.push_str(format!(" (Trainiert für {} epochen)", eps).as_str());
-                        }
+        if let Some(best) = best {
+            let trial = &sweep.trials[best];
+            ui.label(format!("Bester Trial: {}", trial.model_name));
 
-                        if ui
-                            .selectable_label(
-                                self.selected_model == m,
-                                RichText::new(label).color(if is_training {
-                                    YELLOW
-                                } else {
-                                    Color32::GRAY
-                                }),
-                            )
-                            .clicked()
-                        {
-                            self.selected_model = Some(name.clone());
-                            self.create_error("Model geändert", MessageType::Success);
+            ui.horizontal(|ui| {
+                ui.label("Neuer Name:");
+                ui.text_edit_singleline(&mut self.sweep_promote_name);
+
+                let name = self.sweep_promote_name.trim().to_string();
+                if ui
+                    .add_enabled(
+                        !name.is_empty(),
+                        egui::Button::new("Als neues Modell übernehmen"),
+                    )
+                    .on_hover_text("Forkt den besten Trial (Gewichte + Config)")
+                    .clicked()
+                {
+                    match image_data_wrapper::fork_model(&trial.model_name, &name) {
+                        Ok(_) => {
+                            self.create_error(
+                                format!("{} aus {} erstellt", name, trial.model_name),
+                                MessageType::Success,
+                            );
+                            self.sweep_promote_name.clear();
+                            self.reload_models();
                         }
+                        Err(e) => self.create_error(
+                            format!("Konnte {} nicht übernehmen: {:?}", trial.model_name, e),
+                            MessageType::Error,
+                        ),
                     }
-                });
+                }
 
-            for thrd in self.train_threads.iter_mut() {
-                if thrd.model_name == self.selected_model && self.selected_model.is_some() {
-                    if thrd.is_running() {
-                        let text = "Stop Training";
-                        if ui
-                            .add(
-                                egui::Button::new(RichText::new(text).color(Color32::WHITE))
-                                    .fill(RED),
-                            )
-                            .clicked()
-                        {
-                            thrd.stop();
-                            self.create_error("Training gestoppt", MessageType::Success);
+                if ui
+                    .add_enabled(!name.is_empty(), egui::Button::new("Als Preset speichern"))
+                    .clicked()
+                {
+                    match image_data_wrapper::TrainingConfig::save_preset(&name, &trial.config) {
+                        Ok(_) => {
+                            self.training_presets =
+                                image_data_wrapper::TrainingConfig::load_presets();
+                            self.create_error("Preset gespeichert", MessageType::Success);
                         }
-                        return;
+                        Err(e) => self.create_error(
+                            format!("Konnte Preset nicht speichern: {:?}", e),
+                            MessageType::Error,
+                        ),
                     }
                 }
+            });
+        }
+
+        if ui
+            .add(egui::Button::new("Sweep löschen (inkl. Modelle)").fill(RED))
+            .clicked()
+        {
+            match sweep.delete(true, &mut self.training_queue) {
+                Ok(_) => self.create_error("Sweep gelöscht", MessageType::Success),
+                Err(e) => self.create_error(
+                    format!("Sweep konnte nicht gelöscht werden: {:?}", e),
+                    MessageType::Error,
+                ),
             }
+            self.sweeps = sweep::Sweep::load_all();
+            self.selected_sweep = None;
+            self.sweep_results = None;
+            self.reload_models();
+        }
+    }
+
+    fn class_metrics_ui(&mut self, ui: &mut egui::Ui) {
+        let Some((model_name, result)) = &self.class_metrics else {
+            return;
+        };
 
-            if self.selected_model.is_none() {
+        let classes = match result {
+            Ok(classes) => classes,
+            Err(e) => {
+                ui.label(
+                    RichText::new(format!("Keine TestVals für {}: {:?}", model_name, e)).color(RED),
+                );
                 return;
             }
+        };
 
-            ui.horizontal(|ui| {
-                ui.label("epochen:");
-                ui.text_edit_singleline(&mut self.current_epochen);
-            });
-
-            let res = self.current_epochen.trim().parse::<usize>();
+        ui.label(RichText::new(format!("Klassen-Metriken von {}", model_name)).strong());
 
-            let text = "Start Training";
-            if ui
-                .add(
-                    egui::Button::new(RichText::new(text).color(Color32::WHITE)).fill(GREEN), // Grün
-                )
-                .clicked()
-            {
-                if let Err(e) = res {
-                    self.create_error(
-                        format!("Falsche angabe der epochen: {:?}", e),
-                        MessageType::Error,
-                    );
-                    return;
-                }
-                let mut thrd = TrainThread::new();
-                thrd.start(res.unwrap(), self.selected_model.clone().unwrap());
+        // Die schwächsten Klassen nach mAP50-95, unabhängig von der Sortierung
+        let mut by_map: Vec<&image_data_wrapper::ClassMetrics> = classes.iter().collect();
+        by_map.sort_by(|a, b| ClassSort::Map50_95.compare(a, b));
+        let weakest: HashSet<&str> = by_map
+            .iter()
+            .take(WEAKEST_CLASSES)
+            .map(|c| c.class_name.as_str())
+            .collect();
 
-                self.train_threads.push(thrd);
-                self.create_error("Training gestartet", MessageType::Success);
+        let mut sorted = classes.clone();
+        sorted.sort_by(|a, b| {
+            let ord = self.class_metrics_sort.compare(a, b);
+            if self.class_metrics_ascending {
+                ord
+            } else {
+                ord.reverse()
             }
         });
-    }
 
-    fn model(&mut self, ui: &mut egui::Ui, ctx: &egui::Context) {
-        ui.separator();
-        self.manage_models(ui);
-        ui.separator();
-        self.model_training(ui);
-        ui.separator();
-        self.model_testen(ui, ctx);
-        ui.separator();
-        ui.collapsing("TestVals", |ui| {
-            self.show_selectable_models(ui);
-            if let Some(sm) = self.selected_model.clone() {
-                if ui.button("Generate TestVals").clicked() {
-                    self.create_error("Gereating TestVals...", MessageType::Success);
-                    let res = image_data_wrapper::get_testvals(sm);
-                    if let Ok(_) = res {
-                        self.create_error("Gereated TestVals", MessageType::Success);
-                    } else if let Err(fe) = res {
-                        self.create_error(
-                            format!("failed getting testvals: {:?}", fe),
-                            MessageType::Error,
-                        );
-                    }
-                }
+        let mut clicked_sort = None;
+
+        egui::ScrollArea::vertical()
+            .max_height(400.0)
+            .show(ui, |ui| {
+                egui::Grid::new("class_metrics_grid")
+                    .striped(true)
+                    .show(ui, |ui| {
+                        for (sort, title) in [
+                            (ClassSort::Name, "Klasse"),
+                            (ClassSort::Images, "Bilder"),
+                            (ClassSort::Instances, "Instanzen"),
+                            (ClassSort::Precision, "P"),
+                            (ClassSort::Recall, "R"),
+                            (ClassSort::Map50, "mAP50"),
+                            (ClassSort::Map50_95, "mAP50-95"),
+                        ] {
+                            let arrow = match (
+                                sort == self.class_metrics_sort,
+                                self.class_metrics_ascending,
+                            ) {
+                                (true, true) => " ⏶",
+                                (true, false) => " ⏷",
+                                _ => "",
+                            };
+                            if ui
+                                .selectable_label(
+                                    sort == self.class_metrics_sort,
+                                    format!("{}{}", title, arrow),
+                                )
+                                .clicked()
+                            {
+                                clicked_sort = Some(sort);
+                            }
+                        }
+                        ui.end_row();
+
+                        for class in sorted.iter() {
+                            let color = if weakest.contains(class.class_name.as_str()) {
+                                RED
+                            } else {
+                                ui.visuals().text_color()
+                            };
+                            let cell = |ui: &mut egui::Ui, text: String| {
+                                ui.label(RichText::new(text).color(color));
+                            };
+
+                            cell(ui, class.class_name.clone());
+                            cell(ui, class.images.to_string());
+                            cell(ui, class.instances.to_string());
+                            cell(ui, format!("{:.3}", class.precision));
+                            cell(ui, format!("{:.3}", class.recall));
+                            cell(ui, format!("{:.3}", class.map_50));
+                            cell(ui, format!("{:.3}", class.map_50_95));
+                            ui.end_row();
+                        }
+                    });
+            });
+
+        if let Some(sort) = clicked_sort {
+            if sort == self.class_metrics_sort {
+                self.class_metrics_ascending = !self.class_metrics_ascending;
+            } else {
+                self.class_metrics_sort = sort;
+                self.class_metrics_ascending = true;
             }
-        });
+        }
     }
 
     fn extract_numbers(s: &str) -> Vec<i32> {
@@ -2519,7 +5065,7 @@ impl ScreenshotApp {
         prefix.to_string()
     }
 
-    fn save_labeld_rects(&mut self, levels_only: bool, buildings_only: bool) {
+    fn save_labeld_rects(&mut self, datasets: Vec<DatasetType>) {
         if let Some(image_path) = self.labeling_que.clone().last() {
             let mut rng = rand::thread_rng();
             self.create_error("Speichere YOLO-Labels...", MessageType::Success);
@@ -2548,15 +5094,19 @@ impl ScreenshotApp {
                 names: HashMap<usize, String>,
             }
 
-            let mut dataset_paths = vec![
-                ("dataset_buildings", Regex::new(r"\D+").unwrap()), // Nur Buchstaben
-                ("dataset_level", Regex::new(r"\d+").unwrap()),     // Nur Ziffern
-            ];
-
-            if buildings_only {
-                dataset_paths.pop();
-            } else if levels_only {
-                dataset_paths = vec![dataset_paths.last().unwrap().clone()];
+            // Label-Regel kommt aus dem Datensatz (z. B. Buchstaben für buildings, Ziffern für level)
+            let mut dataset_paths = vec![];
+            for dataset_type in datasets.iter() {
+                let Some(rule) = &dataset_type.label_regex else {
+                    continue;
+                };
+                match Regex::new(rule) {
+                    Ok(label_regex) => dataset_paths.push((dataset_type.dir(), label_regex)),
+                    Err(e) => self.create_error(
+                        &format!("Label-Regel von {} ungültig: {}", dataset_type.name, e),
+                        MessageType::Error,
+                    ),
+                }
             }
 
             for (idx, (dataset_base, label_regex)) in dataset_paths.iter().enumerate() {
@@ -2739,7 +5289,7 @@ impl ScreenshotApp {
                         *id
                     } else if true {
                         //jetzt erstmal keine neuen class_ids zu data.yaml hinzufügen
-                        self.create_error("Label not found in buildings data.yaml!!! (was hat bro schon wieder getan)", MessageType::Error);
+                        self.create_error(format!("Label not found in {}/data.yaml!!! (was hat bro schon wieder getan)", dataset_base), MessageType::Error);
                         println!("label that was not found: {}", extracted_label);
                         continue;
                     } else {
@@ -2899,6 +5449,92 @@ impl ScreenshotApp {
             });
     }
 
+    fn batch_prediction_ui(&mut self, ui: &mut egui::Ui) {
+        ui.heading("Vorhersagen für ausgewählte Bilder");
+
+        if let Some(batch) = self.batch_prediction.as_mut() {
+            let errors = batch.update();
+            for e in errors {
+                self.create_error(
+                    format!("Vorhersage fehlgeschlagen: {}", e),
+                    MessageType::Error,
+                );
+            }
+        }
+
+        if let Some(batch) = &self.batch_prediction {
+            let fraction = if batch.total == 0 {
+                1.0
+            } else {
+                batch.done as f32 / batch.total as f32
+            };
+
+            ui.label(format!(
+                "Modell: {} → {}",
+                batch.model_name,
+                batch.output_dir.display()
+            ));
+            ui.add(egui::ProgressBar::new(fraction).text(format!(
+                "{}/{} ({} Fehler)",
+                batch.done, batch.total, batch.failed
+            )));
+
+            if batch.finished {
+                let (msg, kind) = if let Some(e) = &batch.error {
+                    (
+                        format!("Vorhersagen konnten nicht starten: {:?}", e),
+                        MessageType::Error,
+                    )
+                } else {
+                    let msg = format!(
+                        "{} Vorhersagen gespeichert in {}",
+                        batch.succeeded,
+                        batch.output_dir.display()
+                    );
+                    if batch.failed == 0 {
+                        (msg, MessageType::Success)
+                    } else {
+                        (msg, MessageType::Warning)
+                    }
+                };
+                self.batch_prediction = None;
+                self.create_error(msg, kind);
+            } else {
+                if ui.button("⏹ Abbrechen").clicked() {
+                    batch.cancel();
+                }
+                ui.ctx().request_repaint();
+            }
+            return;
+        }
+
+        self.show_selectable_models(ui, true);
+
+        let can_start = self.selected_model.is_some() && !self.selected_images.is_empty();
+        if ui
+            .add_enabled(
+                can_start,
+                egui::Button::new(format!(
+                    "🔮 Vorhersagen für {} Bilder speichern",
+                    self.selected_images.len()
+                )),
+            )
+            .clicked()
+        {
+            if let Some(output_dir) = rfd::FileDialog::new().pick_folder() {
+                let mut images: Vec<String> = self.selected_images.iter().cloned().collect();
+                images.sort();
+
+                self.batch_prediction = Some(BatchPrediction::start(
+                    self.selected_model.clone().unwrap(),
+                    images,
+                    output_dir,
+                ));
+                self.create_error("Vorhersagen gestartet", MessageType::Success);
+            }
+        }
+    }
+
     fn session_button(&mut self, ui: &mut egui::Ui) {
         let is_running = !self.labeling_que.is_empty();
 
@@ -2995,19 +5631,22 @@ impl ScreenshotApp {
     ) {
         if !skip {
             if !janein {
-                self.save_labeld_rects(false, false);
+                self.save_labeld_rects(DatasetType::labeling_targets());
             } else {
                 let slv = self.selected_lvls_model.is_none();
                 let slb = self.selected_build_model.is_none();
-                if slv && slb {
+                let only = if slv && slb {
                     panic!()
-                } else if slv && !slb {
-                    self.save_labeld_rects(false, true);
-                } else if !slv && slb {
-                    self.save_labeld_rects(true, false);
-                } else if !slv && !slb {
-                    self.save_labeld_rects(true, true);
-                }
+                } else if !slb {
+                    DatasetType::BUILDINGS
+                } else {
+                    DatasetType::LEVEL
+                };
+                let datasets = DatasetType::labeling_targets()
+                    .into_iter()
+                    .filter(|d| d.name == only)
+                    .collect();
+                self.save_labeld_rects(datasets);
             }
         }
 
@@ -3111,6 +5750,9 @@ impl ScreenshotApp {
                     }
                 });
             });
+            ui.group(|ui| {
+                self.batch_prediction_ui(ui);
+            });
             ui.horizontal(|ui| {
                 ui.label("Labeling Typ:");
 
@@ -3168,46 +5810,45 @@ impl ScreenshotApp {
                     .show_ui(ui, |ui| {
                         for model in self.current_models.clone() {
                             let score = model.rating;
-                            let name = model.name;
-
-                            let label = format!(
-                                "{name} ({score:.2}) Typ: {}",
-                                match model.dataset_type {
-                                    image_data_wrapper::DatasetType::Buildings =>
-                                        "🏗️ Building Model",
-                                    image_data_wrapper::DatasetType::Level => "🎯 Level Model",
-                                }
-                            );
-                            let dataset_type = image_data_wrapper::get_dataset_type(&name).unwrap();
+                            let name = model.name.clone();
+
+                            let label = model_label(&model);
+                            let Some(dataset_type) = model.dataset_type.clone() else {
+                                ui.add_enabled(false, egui::Label::new(label))
+                                    .on_disabled_hover_text(model_tooltip(&model));
+                                continue;
+                            };
 
                             if ui
-                                .selectable_label(
-                                    self.selected_lvls_model.as_deref() == Some(&name)
-                                        || self.selected_build_model.as_deref() == Some(&name),
-                                    RichText::new(label).color(
-                                        if dataset_type
-                                            == image_data_wrapper::DatasetType::Buildings
-                                        {
-                                            Color32::BROWN
-                                        } else if dataset_type
-                                            == image_data_wrapper::DatasetType::Level
-                                        {
-                                            Color32::PURPLE
-                                        } else {
-                                            unreachable!()
-                                        },
-                                    ),
+                                .add_enabled_ui(
+                                    model.is_usable()
+                                        && (dataset_type.is_buildings() || dataset_type.is_level()),
+                                    |ui| {
+                                        ui.selectable_label(
+                                            self.selected_lvls_model.as_deref() == Some(&name)
+                                                || self.selected_build_model.as_deref()
+                                                    == Some(&name),
+                                            RichText::new(label).color(
+                                                if dataset_type.is_buildings() {
+                                                    Color32::BROWN
+                                                } else {
+                                                    Color32::PURPLE
+                                                },
+                                            ),
+                                        )
+                                    },
                                 )
+                                .inner
+                                .on_hover_text(model_tooltip(&model))
                                 .clicked()
                             {
                                 if Some(name.clone()) == self.selected_build_model {
                                     self.selected_build_model = None;
                                 } else if Some(name.clone()) == self.selected_lvls_model {
                                     self.selected_lvls_model = None;
-                                } else if dataset_type == image_data_wrapper::DatasetType::Buildings
-                                {
+                                } else if dataset_type.is_buildings() {
                                     self.selected_build_model = Some(name);
-                                } else if dataset_type == image_data_wrapper::DatasetType::Level {
+                                } else if dataset_type.is_level() {
                                     self.selected_lvls_model = Some(name);
                                 }
 
@@ -3278,6 +5919,19 @@ impl ScreenshotApp {
     }
 
     fn start_getting_builds(&mut self, img: String) {
+        // Gespeicherte Schwellen pro Klasse, auch wenn die Tabelle nie aufgeklappt wird
+        for model in [
+            self.selected_build_model.clone(),
+            self.selected_lvls_model.clone(),
+        ]
+        .into_iter()
+        .flatten()
+        {
+            self.class_thresholds
+                .entry(model.clone())
+                .or_insert_with(|| class_thresholds::ClassThresholds::load(&model));
+        }
+
         if let Some(mdl) = &self.selected_build_model {
             self.current_avg_conf_build = None;
             self.current_buildings_build = None;
@@ -3289,6 +5943,17 @@ impl ScreenshotApp {
                 .set_field("model_name", mdl.to_string());
             self.get_building_thread_build
                 .set_field("path_to_image", img.to_string());
+            self.current_native_build = None;
+            self.get_building_thread_build.set_field(
+                "native_buildings",
+                Err::<Vec<image_data_wrapper::Building>, FofError>(FofError::ThreadNotInitialized),
+            );
+            self.get_building_thread_build
+                .set_field("compare_native", self.compare_native);
+            self.get_building_thread_build.set_field(
+                "tiling",
+                self.tiled_prediction.then_some(self.tile_settings),
+            );
             self.get_building_thread_build
                 .set_field("should_get_prediction", true);
             self.get_building_thread_build
@@ -3305,6 +5970,17 @@ impl ScreenshotApp {
                 .set_field("model_name", mdl.to_string());
             self.get_building_thread_lvls
                 .set_field("path_to_image", img.to_string());
+            self.current_native_lvls = None;
+            self.get_building_thread_lvls.set_field(
+                "native_buildings",
+                Err::<Vec<image_data_wrapper::Building>, FofError>(FofError::ThreadNotInitialized),
+            );
+            self.get_building_thread_lvls
+                .set_field("compare_native", self.compare_native);
+            self.get_building_thread_lvls.set_field(
+                "tiling",
+                self.tiled_prediction.then_some(self.tile_settings),
+            );
             self.get_building_thread_lvls
                 .set_field("should_get_prediction", true);
             self.get_building_thread_lvls
@@ -3416,7 +6092,122 @@ impl ScreenshotApp {
         }
     }
 
+    fn metrics_overview(&mut self, ui: &mut egui::Ui) {
+        use egui_plot::{Legend, Line, Plot, PlotPoints, Points, VLine};
+
+        ui.heading("Metriken");
+
+        ui.horizontal_wrapped(|ui| {
+            ui.label("Modelle:");
+            for model in self.current_models.clone() {
+                if !matches!(
+                    model.status,
+                    image_data_wrapper::ModelStatus::Trained
+                        | image_data_wrapper::ModelStatus::Training
+                        | image_data_wrapper::ModelStatus::Corrupt
+                ) {
+                    continue;
+                }
+
+                let mut checked = self.overview_models.contains(&model.name);
+                if ui
+                    .checkbox(&mut checked, &model.name)
+                    .on_hover_text(model_tooltip(&model))
+                    .changed()
+                {
+                    if checked {
+                        self.overview_models.push(model.name.clone());
+                    } else {
+                        self.overview_models.retain(|m| m != &model.name);
+                        self.overview_histories.remove(&model.name);
+                    }
+                }
+            }
+        });
+
+        ui.horizontal_wrapped(|ui| {
+            ui.label("Werte:");
+            for column in image_data_wrapper::MetricColumn::iter() {
+                let mut checked = self.overview_metrics.contains(&column);
+                if ui.checkbox(&mut checked, column.to_string()).changed() {
+                    if checked {
+                        self.overview_metrics.insert(column);
+                    } else {
+                        self.overview_metrics.remove(&column);
+                    }
+                }
+            }
+        });
+
+        if ui.button("🔄 Neu laden").clicked() {
+            self.overview_histories.clear();
+        }
+
+        for model in self.overview_models.iter() {
+            if !self.overview_histories.contains_key(model) {
+                self.overview_histories.insert(
+                    model.clone(),
+                    image_data_wrapper::get_metrics_history(model),
+                );
+            }
+        }
+
+        for model in self.overview_models.clone() {
+            if let Some(Err(e)) = self.overview_histories.get(&model) {
+                ui.label(RichText::new(format!("{}: {:?}", model, e)).color(RED));
+            }
+        }
+
+        // Spalten in fester Reihenfolge, damit die Farben nicht springen
+        let columns: Vec<_> = image_data_wrapper::MetricColumn::iter()
+            .filter(|c| self.overview_metrics.contains(c))
+            .collect();
+
+        Plot::new("metrics_overview_plot")
+            .height(350.0)
+            .legend(Legend::default())
+            .x_axis_label("Epoche")
+            .show(ui, |plot_ui| {
+                for model in self.overview_models.iter() {
+                    let Some(Ok(history)) = self.overview_histories.get(model) else {
+                        continue;
+                    };
+
+                    for column in columns.iter() {
+                        let points: PlotPoints = history
+                            .iter()
+                            .map(|e| [e.epoch as f64, column.value(e)])
+                            .collect();
+                        plot_ui.line(Line::new(
+                            format!("{} {}", model, column.to_string()),
+                            points,
+                        ));
+                    }
+
+                    // Beste Epoche markieren (gleiche Formel wie das Rating)
+                    if let Some(best) =
+                        image_data_wrapper::best_epoch(history, &self.rating_formula)
+                    {
+                        let name = format!("{} beste Epoche ({})", model, best.epoch);
+                        plot_ui.vline(VLine::new(name.clone(), best.epoch as f64));
+                        for column in columns.iter() {
+                            plot_ui.points(
+                                Points::new(
+                                    name.clone(),
+                                    vec![[best.epoch as f64, column.value(best)]],
+                                )
+                                .radius(4.0),
+                            );
+                        }
+                    }
+                }
+            });
+    }
+
     fn model_übersicht(&mut self, ui: &mut egui::Ui, ctx: &egui::Context) {
+        self.metrics_overview(ui);
+        ui.separator();
+
         ui.collapsing("FOF", |ui| {
             ui.collapsing("FOF", |ui| {
                 ui.collapsing("FOF", |ui| {
@@ -3512,15 +6303,41 @@ impl eframe::App for ScreenshotApp {
             }
             let mut errors = vec![];
 
-            for thrd in &self.train_threads {
-                if let Some(msg) = &thrd.msg {
-                    let kind = if msg == "Trainging Fertig, fängt Automatisch Neues An" {
-                        MessageType::Success
-                    } else {
-                        MessageType::Error
-                    };
-                    errors.push((kind, msg.clone()));
-                }
+            for event in self.training_queue.tick() {
+                errors.push(match event {
+                    training_queue::QueueEvent::Started(model) => {
+                        self.reload_models();
+                        (
+                            MessageType::Success,
+                            format!("Training von {} gestartet", model),
+                        )
+                    }
+                    training_queue::QueueEvent::Reattached(model) => (
+                        MessageType::Success,
+                        format!("Laufendes Training von {} gefunden", model),
+                    ),
+                    training_queue::QueueEvent::Finished(model) => {
+                        self.on_training_done(&model);
+                        (
+                            MessageType::Success,
+                            format!("Training von {} fertig", model),
+                        )
+                    }
+                    training_queue::QueueEvent::Stopped(model) => {
+                        self.on_training_done(&model);
+                        (
+                            MessageType::Warning,
+                            format!("Training von {} gestoppt, kann fortgesetzt werden", model),
+                        )
+                    }
+                    training_queue::QueueEvent::Failed(model, reason) => {
+                        self.on_training_done(&model);
+                        (
+                            MessageType::Error,
+                            format!("Training von {} fehlgeschlagen: {}", model, reason),
+                        )
+                    }
+                });
             }
 
             for (kind, msg) in errors {